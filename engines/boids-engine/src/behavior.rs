@@ -0,0 +1,249 @@
+use crate::{Boid, Hit, Interaction, Neighbor, NeighborMode, Params, Vec2, World};
+
+/*
+ * note:
+ * a behavior returns the desired velocity of one boid as a fraction of max_speed,
+ * usually of magnitude <= 1, or zero when it has no opinion.
+ * World turns it into a steering force (desired - current velocity, clamped to max_force)
+ * and scales it by the behavior weight (Params fields for the built-in ones).
+ * Send + Sync so a World with custom behaviors can still move to another thread
+ */
+pub trait Behavior: Send + Sync {
+    fn steer(&self, boid: &Boid, neighbors: &[Neighbor], world: &World) -> Vec2;
+}
+
+pub struct Separation;
+pub struct Alignment;
+pub struct Cohesion;
+pub struct Attraction; // force fields, including the mouse attractor
+pub struct Avoidance; // obstacles
+pub struct Flee; // predators
+pub struct FollowLeader; // formation slot of the boid leader, if any
+pub struct FollowFlow; // world flow field, if any
+
+// straight towards a fixed point
+pub struct Seek {
+    pub target: Vec2,
+}
+
+// towards a fixed point, slowing down inside slowing_radius
+pub struct Arrive {
+    pub target: Vec2,
+    pub slowing_radius: f32,
+}
+
+impl Behavior for Separation {
+    fn steer(&self, boid: &Boid, neighbors: &[Neighbor], world: &World) -> Vec2 {
+        let radius = world.get_species_params(boid.species).separation_radius;
+        let boids = world.get_boids();
+        let mut force = Vec2::ZERO;
+        let mut count = 0;
+
+        for neighbor in neighbors {
+            if neighbor.dist > 0.0 && neighbor.dist < radius {
+                force -= (boids[neighbor.index].pos - boid.pos).normalize() / neighbor.dist;
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            force.normalize()
+        } else {
+            Vec2::ZERO
+        }
+    }
+}
+
+impl Behavior for Alignment {
+    fn steer(&self, boid: &Boid, neighbors: &[Neighbor], world: &World) -> Vec2 {
+        let params = world.get_species_params(boid.species);
+        let radius = flock_radius(params, neighbors);
+        let boids = world.get_boids();
+        let mut avg_vel = Vec2::ZERO;
+        let mut total = 0.0;
+
+        for neighbor in neighbors {
+            let other = &boids[neighbor.index];
+
+            if neighbor.dist >= radius || world.get_interaction(boid.species, other.species) != Interaction::Flock {
+                continue;
+            }
+
+            let weight = weight(params, neighbor.dist, radius);
+            avg_vel += other.vel * weight;
+            total += weight;
+        }
+
+        if total > 0.0 {
+            (avg_vel / total).normalize()
+        } else {
+            Vec2::ZERO
+        }
+    }
+}
+
+impl Behavior for Cohesion {
+    fn steer(&self, boid: &Boid, neighbors: &[Neighbor], world: &World) -> Vec2 {
+        let params = world.get_species_params(boid.species);
+        let radius = flock_radius(params, neighbors);
+        let boids = world.get_boids();
+        let mut center = Vec2::ZERO;
+        let mut total = 0.0;
+
+        for neighbor in neighbors {
+            let other = &boids[neighbor.index];
+
+            if neighbor.dist >= radius || world.get_interaction(boid.species, other.species) != Interaction::Flock {
+                continue;
+            }
+
+            let weight = weight(params, neighbor.dist, radius);
+            center += other.pos * weight;
+            total += weight;
+        }
+
+        if total > 0.0 {
+            ((center / total) - boid.pos).normalize()
+        } else {
+            Vec2::ZERO
+        }
+    }
+}
+
+impl Behavior for Attraction {
+    fn steer(&self, boid: &Boid, _neighbors: &[Neighbor], world: &World) -> Vec2 {
+        let mut force = Vec2::ZERO;
+
+        for (_, field) in world.get_fields() {
+            force += field.force(boid.pos);
+        }
+
+        force
+    }
+}
+
+impl Behavior for Avoidance {
+    fn steer(&self, boid: &Boid, _neighbors: &[Neighbor], world: &World) -> Vec2 {
+        let dir = boid.vel.normalize();
+        let look_ahead = world.get_species_params(boid.species).look_ahead;
+
+        let nearest = world.get_obstacles()
+            .iter()
+            .filter_map(|obstacle| obstacle.raycast(boid.pos, dir, look_ahead))
+            .min_by(|a, b| a.distance.total_cmp(&b.distance));
+
+        match nearest {
+            // already inside, push straight out
+            Some(Hit { distance, normal }) if distance <= 0.0 => normal,
+
+            Some(Hit { distance, normal }) => {
+                // turn using the part of the normal perpendicular to the heading, instead of braking
+                let lateral = normal - dir * normal.dot(dir);
+                let lateral = if lateral.magnitude() > 1e-3 { lateral } else { dir.perp() };
+                let urgency = 1.0 - distance / look_ahead;
+
+                (dir * (1.0 - urgency) + lateral.normalize() * urgency).normalize()
+            }
+
+            None => Vec2::ZERO,
+        }
+    }
+}
+
+impl Behavior for Flee {
+    fn steer(&self, boid: &Boid, _neighbors: &[Neighbor], world: &World) -> Vec2 {
+        let radius = world.get_species_params(boid.species).flee_radius;
+        let mut force = Vec2::ZERO;
+        let mut count = 0;
+
+        for predator in world.get_predators() {
+            let diff = boid.pos - predator.pos;
+            let dist = diff.magnitude();
+
+            if dist > 0.0 && dist < radius {
+                force += diff.normalize() * (1.0 - dist / radius);
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            force.normalize()
+        } else {
+            Vec2::ZERO
+        }
+    }
+}
+
+impl Behavior for FollowLeader {
+    fn steer(&self, boid: &Boid, _neighbors: &[Neighbor], world: &World) -> Vec2 {
+        let Some((index, slot)) = boid.leader else {
+            return Vec2::ZERO;
+        };
+
+        let Some(leader) = world.get_leaders().get(index) else {
+            return Vec2::ZERO;
+        };
+
+        let params = world.get_species_params(boid.species);
+        let diff = leader.slot_position(slot) - boid.pos;
+
+        // match the leader, plus arrive at the slot
+        let arrive = if params.arrival_radius > 0.0 {
+            diff.normalize() * (diff.magnitude() / params.arrival_radius).min(1.0)
+        } else {
+            diff.normalize()
+        };
+
+        // max_speed can be 0.0, and a leader at the end of an open path stands still
+        let matching = if params.max_speed > 0.0 { leader.vel / params.max_speed } else { Vec2::ZERO };
+
+        (matching + arrive).limit(1.0)
+    }
+}
+
+impl Behavior for FollowFlow {
+    fn steer(&self, boid: &Boid, _neighbors: &[Neighbor], world: &World) -> Vec2 {
+        let Some(field) = world.get_flow_field() else {
+            return Vec2::ZERO;
+        };
+
+        let resolution = world.get_species_params(boid.species).flow_resolution;
+
+        field.sample(boid.pos, world.get_width(), world.get_height(), resolution)
+    }
+}
+
+impl Behavior for Seek {
+    fn steer(&self, boid: &Boid, _neighbors: &[Neighbor], _world: &World) -> Vec2 {
+        (self.target - boid.pos).normalize()
+    }
+}
+
+impl Behavior for Arrive {
+    fn steer(&self, boid: &Boid, _neighbors: &[Neighbor], _world: &World) -> Vec2 {
+        let diff = self.target - boid.pos;
+        let dist = diff.magnitude();
+
+        if self.slowing_radius <= 0.0 {
+            return diff.normalize();
+        }
+
+        diff.normalize() * (dist / self.slowing_radius).min(1.0)
+    }
+}
+
+// alignment and cohesion reach, metric radius or the farthest of the k neighbours
+fn flock_radius(params: &Params, neighbors: &[Neighbor]) -> f32 {
+    match params.neighbor_mode {
+        NeighborMode::Metric => params.perception_radius,
+        NeighborMode::Topological => neighbors.iter().map(|n| n.dist).fold(0.0, f32::max).next_up(),
+    }
+}
+
+fn weight(params: &Params, dist: f32, radius: f32) -> f32 {
+    if params.distance_weighting {
+        1.0 - dist / radius
+    } else {
+        1.0
+    }
+}
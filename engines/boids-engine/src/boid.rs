@@ -1,12 +1,44 @@
+use std::collections::VecDeque;
+
 use crate::Vec2;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Boid {
     pub pos: Vec2,
     pub vel: Vec2,
+    pub species: usize, // index into the world species params
+
+    pub prev_pos: Vec2, // before the last step, for render interpolation
+    pub acc: Vec2, // of the last step
+
+    pub neighbors: usize, // perceived in the last step
+    pub density: f32, // perceived neighbours per px^2 of the perception disc, last step
+
+    pub trail: VecDeque<Vec2>, // oldest first, see World::set_trail_length
+
+    pub leader: Option<(usize, usize)>, // (leader, formation slot) when following one
 }
 
 impl Boid {
     pub fn new(pos: Vec2, vel: Vec2) -> Self {
-        Self { pos, vel }
+        Self::with_species(pos, vel, 0)
+    }
+
+    pub fn with_species(pos: Vec2, vel: Vec2, species: usize) -> Self {
+        Self { pos, vel, species, prev_pos: pos, acc: Vec2::ZERO, neighbors: 0, density: 0.0, trail: VecDeque::new(), leader: None }
+    }
+
+    // radians, 0.0 = +x, towards +y
+    pub fn heading(&self) -> f32 {
+        self.vel.y.atan2(self.vel.x)
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.vel.magnitude()
+    }
+
+    // alpha from World::advance, 0.0 = previous step, 1.0 = current step
+    pub fn interpolate(&self, alpha: f32) -> Vec2 {
+        self.prev_pos + (self.pos - self.prev_pos) * alpha
     }
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+use crate::Vec2;
+use crate::math::{closest_on_segment, polygon_contains};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Boundary {
+    Bounce, // clamped onto the edge, velocity reflected
+    Wrap, // toroidal world, containment regions still bounce
+    SoftMargin { margin: f32, force: f32 }, // inward acceleration within margin px of the edge, bounce as a last resort
+    Open, // leaving boids are respawned on the edge, heading inwards
+}
+
+// area boids are kept inside of, on top of the world rectangle
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Region {
+    Circle { center: Vec2, radius: f32 },
+    Polygon { points: Vec<Vec2> }, // closed, any winding
+}
+
+impl Region {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Region::Circle { radius, .. } => *radius > 0.0,
+            Region::Polygon { points } => points.len() >= 3,
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Region::Circle { center, radius } => (point - *center).magnitude() <= *radius,
+
+            Region::Polygon { points } => polygon_contains(points, point),
+        }
+    }
+
+    // (distance, closest point on the boundary, normal pointing into the region there)
+    pub fn boundary(&self, point: Vec2) -> (f32, Vec2, Vec2) {
+        match self {
+            Region::Circle { center, radius } => {
+                let offset = point - *center;
+                let dir = if offset.magnitude() > 0.0 { offset.normalize() } else { Vec2::new(1.0, 0.0) };
+                let closest = *center + dir * *radius;
+
+                ((offset.magnitude() - radius).abs(), closest, -dir)
+            }
+
+            Region::Polygon { points } => {
+                let n = points.len();
+
+                // the inside is left of every edge when the signed area is positive
+                let area: f32 = (0..n).map(|i| points[i].cross(points[(i + 1) % n])).sum();
+                let winding = if area >= 0.0 { 1.0 } else { -1.0 };
+                let inward = |i: usize| (points[(i + 1) % n] - points[i]).perp().normalize() * winding;
+
+                let mut best = (f32::INFINITY, points[0], Vec2::ZERO);
+
+                for i in 0..n {
+                    let a = points[i];
+                    let b = points[(i + 1) % n];
+                    let closest = closest_on_segment(a, b, point);
+                    let distance = (point - closest).magnitude();
+
+                    if distance < best.0 {
+                        // on a corner, between both edge normals so a push clears the two edges
+                        let normal = if (closest - a).magnitude() == 0.0 {
+                            inward((i + n - 1) % n) + inward(i)
+                        } else if (closest - b).magnitude() == 0.0 {
+                            inward(i) + inward((i + 1) % n)
+                        } else {
+                            inward(i)
+                        };
+
+                        best = (distance, closest, normal.normalize());
+                    }
+                }
+
+                best
+            }
+        }
+    }
+
+    // (min, max) corners of the bounding box
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        match self {
+            Region::Circle { center, radius } => {
+                (*center - Vec2::new(*radius, *radius), *center + Vec2::new(*radius, *radius))
+            }
+
+            Region::Polygon { points } => points.iter().fold(
+                (Vec2::new(f32::INFINITY, f32::INFINITY), Vec2::new(f32::NEG_INFINITY, f32::NEG_INFINITY)),
+                |(min, max), p| (Vec2::new(min.x.min(p.x), min.y.min(p.y)), Vec2::new(max.x.max(p.x), max.y.max(p.y))),
+            ),
+        }
+    }
+}
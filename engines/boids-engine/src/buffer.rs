@@ -0,0 +1,40 @@
+use crate::Boid;
+
+/*
+ * note:
+ * interleaved per-boid floats, one record of BUFFER_STRIDE floats per boid,
+ * so renderers (e.g. a Float32Array over wasm memory) can read it without copying
+ */
+pub const BUFFER_STRIDE: usize = 9;
+
+// (name, offset in floats within a record)
+pub const BUFFER_LAYOUT: [(&str, usize); BUFFER_STRIDE] = [
+    ("x", 0),
+    ("y", 1),
+    ("vx", 2),
+    ("vy", 3),
+    ("heading", 4), // radians, atan2(vy, vx)
+    ("species", 5),
+    ("speed", 6),
+    ("neighbors", 7),
+    ("density", 8), // perceived neighbours per px^2
+];
+
+// reuses the allocation while the population doesn't grow
+pub(crate) fn write_buffer(boids: &[Boid], buffer: &mut Vec<f32>) {
+    buffer.clear();
+
+    for boid in boids {
+        buffer.extend_from_slice(&[
+            boid.pos.x,
+            boid.pos.y,
+            boid.vel.x,
+            boid.vel.y,
+            boid.heading(),
+            boid.species as f32,
+            boid.speed(),
+            boid.neighbors as f32,
+            boid.density,
+        ]);
+    }
+}
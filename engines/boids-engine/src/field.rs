@@ -0,0 +1,60 @@
+use crate::Vec2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldKind {
+    Attractor,
+    Repeller,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Falloff {
+    Constant, // full strength inside radius, nothing beyond
+    Linear, // fades from full strength to 0.0 at radius
+    InverseSquare, // full strength inside radius, (radius / dist)^2 beyond
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForceField {
+    pub kind: FieldKind,
+    pub pos: Vec2,
+    pub strength: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::unbounded"))]
+    pub radius: f32, // f32::INFINITY for unbounded
+    pub falloff: Falloff,
+}
+
+impl ForceField {
+    pub fn new(kind: FieldKind, pos: Vec2, strength: f32, radius: f32, falloff: Falloff) -> Self {
+        Self { kind, pos, strength, radius, falloff }
+    }
+
+    // constant pull at any distance, like the original single attractor
+    pub fn attractor(pos: Vec2) -> Self {
+        Self::new(FieldKind::Attractor, pos, 1.0, f32::INFINITY, Falloff::Constant)
+    }
+
+    pub fn force(&self, pos: Vec2) -> Vec2 {
+        let diff = self.pos - pos;
+        let dist = diff.magnitude();
+
+        if dist <= 0.0 {
+            return Vec2::ZERO;
+        }
+
+        let factor = match self.falloff {
+            Falloff::Constant => if dist < self.radius { 1.0 } else { 0.0 },
+            Falloff::Linear => (1.0 - dist / self.radius).max(0.0),
+            Falloff::InverseSquare => (self.radius / dist).powi(2).min(1.0),
+        };
+
+        let dir = match self.kind {
+            FieldKind::Attractor => diff.normalize(),
+            FieldKind::Repeller => -diff.normalize(),
+        };
+
+        dir * self.strength * factor
+    }
+}
\ No newline at end of file
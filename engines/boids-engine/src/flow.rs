@@ -0,0 +1,154 @@
+#[cfg(feature = "topography")]
+use topography_engine::Topography;
+
+use crate::Vec2;
+
+/*
+ * references:
+ *
+ * curl noise:
+ * https://www.cs.ubc.ca/~rbridson/docs/bridson-siggraph2007-curlnoise.pdf
+ */
+
+/*
+ * note:
+ * fields are sampled in world coordinates, grids are stretched over the whole world.
+ * samples are at most 1.0 long, they are desired velocities as a fraction of max_speed
+ */
+
+#[cfg(feature = "topography")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopographyFlow {
+    Uphill, // along the gradient
+    Downhill,
+    Contour, // along the contour lines, perpendicular to the gradient
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlowField {
+    Grid { cols: usize, rows: usize, vectors: Vec<Vec2> }, // row major, bilinear between cell centers
+    CurlNoise { scale: f32, seed: u32 }, // scale = feature size in px, divergence-free
+}
+
+impl FlowField {
+    pub fn grid(cols: usize, rows: usize, vectors: Vec<Vec2>) -> Self {
+        assert!(cols > 0 && rows > 0, "Error: grid must have at least one cell");
+        assert!(vectors.len() == cols * rows, "Error: vectors must have cols * rows elements");
+
+        FlowField::Grid { cols, rows, vectors }
+    }
+
+    pub fn curl_noise(scale: f32, seed: u32) -> Self {
+        assert!(scale > 0.0, "Error: scale must be positive");
+
+        FlowField::CurlNoise { scale, seed }
+    }
+
+    // what grid and curl_noise assert, e.g. for restored snapshots
+    pub fn is_valid(&self) -> bool {
+        match self {
+            FlowField::Grid { cols, rows, vectors } => *cols > 0 && *rows > 0 && vectors.len() == cols * rows,
+            FlowField::CurlNoise { scale, .. } => *scale > 0.0,
+        }
+    }
+
+    // unit vectors from central differences of the height map, zero on flat cells
+    #[cfg(feature = "topography")]
+    pub fn from_topography(topography: &Topography, flow: TopographyFlow) -> Self {
+        let map = topography.get_map();
+        let size = topography.get_size();
+        let height = |x: usize, y: usize| map[x + y * size];
+
+        let mut vectors = Vec::with_capacity(size * size);
+
+        for y in 0..size {
+            for x in 0..size {
+                let dx = height((x + 1).min(size - 1), y) - height(x.saturating_sub(1), y);
+                let dy = height(x, (y + 1).min(size - 1)) - height(x, y.saturating_sub(1));
+                let gradient = Vec2::new(dx, dy).normalize();
+
+                vectors.push(match flow {
+                    TopographyFlow::Uphill => gradient,
+                    TopographyFlow::Downhill => -gradient,
+                    TopographyFlow::Contour => gradient.perp(),
+                });
+            }
+        }
+
+        FlowField::grid(size, size, vectors)
+    }
+
+    // resolution > 0.0 snaps pos to the center of resolution-sized cells first
+    pub fn sample(&self, pos: Vec2, width: f32, height: f32, resolution: f32) -> Vec2 {
+        let pos = if resolution > 0.0 {
+            Vec2::new(
+                ((pos.x / resolution).floor() + 0.5) * resolution,
+                ((pos.y / resolution).floor() + 0.5) * resolution,
+            )
+        } else {
+            pos
+        };
+
+        match self {
+            FlowField::Grid { cols, rows, vectors } => {
+                // cell centers at (i + 0.5) * cell size
+                let gx = (pos.x / width * *cols as f32 - 0.5).clamp(0.0, (*cols - 1) as f32);
+                let gy = (pos.y / height * *rows as f32 - 0.5).clamp(0.0, (*rows - 1) as f32);
+
+                let x0 = gx.floor() as usize;
+                let y0 = gy.floor() as usize;
+                let x1 = (x0 + 1).min(cols - 1);
+                let y1 = (y0 + 1).min(rows - 1);
+                let tx = gx - x0 as f32;
+                let ty = gy - y0 as f32;
+
+                let at = |x: usize, y: usize| vectors[x + y * cols];
+                let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * tx;
+                let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * tx;
+
+                (top + (bottom - top) * ty).limit(1.0)
+            }
+
+            FlowField::CurlNoise { scale, seed } => {
+                let x = pos.x / scale;
+                let y = pos.y / scale;
+                let e = 1e-2;
+
+                let dx = value_noise(x + e, y, *seed) - value_noise(x - e, y, *seed);
+                let dy = value_noise(x, y + e, *seed) - value_noise(x, y - e, *seed);
+
+                // curl of a scalar potential, rotated gradient
+                (Vec2::new(dy, -dx) / (2.0 * e)).limit(1.0)
+            }
+        }
+    }
+}
+
+// smooth value noise in [0, 1], lattice values hashed from the cell coordinates
+fn value_noise(x: f32, y: f32, seed: u32) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let tx = smooth(x - x0);
+    let ty = smooth(y - y0);
+
+    let corner = |dx: i32, dy: i32| hash(x0 as i32 + dx, y0 as i32 + dy, seed);
+
+    let top = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * tx;
+    let bottom = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * tx;
+
+    top + (bottom - top) * ty
+}
+
+// quintic, continuous second derivative so the curl has no creases
+fn smooth(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn hash(x: i32, y: i32, seed: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8DA6_B343) ^ (y as u32).wrapping_mul(0xD816_3841) ^ seed.wrapping_mul(0xCB1A_B31F);
+    h = (h ^ (h >> 13)).wrapping_mul(0x5BD1_E995);
+    h ^= h >> 15;
+
+    (h & 0x00FF_FFFF) as f32 / 0x00FF_FFFF as f32
+}
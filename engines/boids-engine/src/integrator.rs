@@ -0,0 +1,15 @@
+/*
+ * references:
+ *
+ * https://gafferongames.com/post/integration_basics/
+ * https://gafferongames.com/post/fix_your_timestep/
+ * https://en.wikipedia.org/wiki/Verlet_integration#Velocity_Verlet
+ */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Integrator {
+    Euler, // position with the old velocity, then velocity
+    SemiImplicitEuler, // velocity first, then position with the new velocity
+    Verlet, // velocity Verlet as kick-drift-kick, vel is at t + dt/2 between steps
+}
\ No newline at end of file
@@ -0,0 +1,102 @@
+use std::f32::consts::TAU;
+
+use crate::{Path, Vec2};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Formation {
+    V, // alternating left and right, one row further back every two slots
+    Line, // abreast, one row behind the leader
+    Circle, // around the leader
+}
+
+// scripted agent moving along a path at constant speed, boids follow it through formation slots
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Leader {
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub prev_pos: Vec2, // before the last step, for render interpolation
+    pub path: Path,
+    pub speed: f32,
+    pub distance: f32, // travelled along the path
+    pub formation: Formation,
+    pub spacing: f32, // between slots
+    pub slots: usize, // assigned by World
+}
+
+impl Leader {
+    pub fn new(path: Path, speed: f32) -> Self {
+        let pos = path.point_at(0.0);
+
+        Self {
+            pos,
+            vel: path.tangent_at(0.0) * speed,
+            prev_pos: pos,
+            path,
+            speed,
+            distance: 0.0,
+            formation: Formation::V,
+            spacing: 20.0,
+            slots: 0,
+        }
+    }
+
+    pub fn heading(&self) -> Vec2 {
+        self.path.tangent_at(self.distance)
+    }
+
+    // world position of a formation slot
+    pub fn slot_position(&self, slot: usize) -> Vec2 {
+        let forward = self.heading();
+        let right = -forward.perp();
+        let offset = self.formation.offset(slot, self.slots, self.spacing);
+
+        self.pos + forward * offset.x + right * offset.y
+    }
+
+    pub fn interpolate(&self, alpha: f32) -> Vec2 {
+        self.prev_pos + (self.pos - self.prev_pos) * alpha
+    }
+
+    pub(crate) fn step(&mut self, dt: f32) {
+        let length = self.path.length();
+        let distance = self.distance + self.speed * dt;
+
+        // open paths stop at the end
+        let finished = !self.path.is_looped() && distance >= length;
+
+        self.prev_pos = self.pos;
+        self.distance = if finished { length } else { distance };
+        self.pos = self.path.point_at(self.distance);
+        self.vel = if finished { Vec2::ZERO } else { self.heading() * self.speed };
+    }
+}
+
+impl Formation {
+    // (forward, right) offset from the leader in its own frame
+    pub fn offset(&self, slot: usize, slots: usize, spacing: f32) -> Vec2 {
+        match self {
+            Formation::V => {
+                let row = (slot / 2 + 1) as f32;
+                let side = if slot.is_multiple_of(2) { -1.0 } else { 1.0 };
+
+                Vec2::new(-row * spacing, side * row * spacing)
+            }
+
+            Formation::Line => {
+                let centered = slot as f32 - (slots.max(1) - 1) as f32 * 0.5;
+
+                Vec2::new(-spacing, centered * spacing)
+            }
+
+            Formation::Circle => {
+                // radius keeping neighbouring slots spacing apart
+                let radius = (slots as f32 * spacing / TAU).max(spacing);
+                let angle = slot as f32 / slots.max(1) as f32 * TAU;
+
+                Vec2::new(angle.cos(), angle.sin()) * radius
+            }
+        }
+    }
+}
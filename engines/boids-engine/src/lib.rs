@@ -3,9 +3,43 @@ mod params;
 mod world;
 mod boid;
 mod random;
+mod obstacle;
+mod predator;
+mod species;
+mod field;
+mod spatial;
+mod behavior;
+mod integrator;
+mod buffer;
+mod stats;
+mod world3d;
+mod path;
+mod leader;
+mod flow;
+mod boundary;
+#[cfg(feature = "serde")]
+mod snapshot;
 
-pub use math::Vec2;
-pub use params::Params;
+pub use math::{Vec2, Vec3};
+pub use params::{NeighborMode, ParamDescriptor, ParamError, ParamKind, Params};
 pub use world::World;
+pub use world3d::{Boid3D, Bounds3D, Projection, World3D};
 pub use boid::Boid;
-pub use random::random_f32;
\ No newline at end of file
+pub use random::{random_f32, Rng};
+pub use integrator::Integrator;
+pub use buffer::{BUFFER_LAYOUT, BUFFER_STRIDE};
+pub use obstacle::{Hit, Obstacle};
+pub use predator::{Capture, Predator};
+pub use path::{Path, PathKind};
+pub use leader::{Formation, Leader};
+pub use flow::FlowField;
+#[cfg(feature = "topography")]
+pub use flow::TopographyFlow;
+pub use boundary::{Boundary, Region};
+pub use species::Interaction;
+pub use field::{Falloff, FieldKind, ForceField};
+pub use spatial::{Neighbor, SpatialGrid};
+pub use stats::{FlockStats, Group};
+#[cfg(feature = "serde")]
+pub use snapshot::SnapshotError;
+pub use behavior::{Alignment, Arrive, Attraction, Avoidance, Behavior, Cohesion, Flee, FollowFlow, FollowLeader, Seek, Separation};
\ No newline at end of file
@@ -1,6 +1,7 @@
-use::std::ops::{Add, Sub, Mul, Div, AddAssign, SubAssign};
+use::std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign};
 
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -27,6 +28,32 @@ impl Vec2 {
         }
     }
 
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    // z component of the 3D cross product
+    pub fn cross(self, rhs: Self) -> f32 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    // counterclockwise perpendicular
+    pub fn perp(self) -> Self {
+        Self { x: -self.y, y: self.x }
+    }
+
+    // counterclockwise, radians
+    pub fn rotate(self, angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+
+        Self { x: self.x * cos - self.y * sin, y: self.x * sin + self.y * cos }
+    }
+
+    // signed, from self to rhs
+    pub fn angle_to(self, rhs: Self) -> f32 {
+        self.cross(rhs).atan2(self.dot(rhs))
+    }
+
     pub fn limit(self, max: f32) -> Self {
         let magnitude = self.magnitude();
 
@@ -70,6 +97,14 @@ impl Div<f32> for Vec2 {
     }
 }
 
+impl Neg for Vec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { x: -self.x, y: -self.y }
+    }
+}
+
 impl AddAssign for Vec2 {
     fn add_assign(&mut self, rhs: Self) {
         self.x += rhs.x;
@@ -82,4 +117,141 @@ impl SubAssign for Vec2 {
         self.x -= rhs.x;
         self.y -= rhs.y;
     }
-}
\ No newline at end of file
+}
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub const ZERO: Self = Self { x: 0.0, y: 0.0, z: 0.0 };
+
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn magnitude(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Self {
+        let magnitude = self.magnitude();
+
+        if magnitude > 0.0 {
+            self / magnitude
+        } else {
+            Self::ZERO
+        }
+    }
+
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn cross(self, rhs: Self) -> Self {
+        Self {
+            x: self.y * rhs.z - self.z * rhs.y,
+            y: self.z * rhs.x - self.x * rhs.z,
+            z: self.x * rhs.y - self.y * rhs.x,
+        }
+    }
+
+    pub fn limit(self, max: f32) -> Self {
+        let magnitude = self.magnitude();
+
+        if magnitude > max {
+            self.normalize() * max
+        } else {
+            self
+        }
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self { x: self.x + rhs.x, y: self.y + rhs.y, z: self.z + rhs.z }
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self { x: self.x - rhs.x, y: self.y - rhs.y, z: self.z - rhs.z }
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        Self { x: self.x * rhs, y: self.y * rhs, z: self.z * rhs }
+    }
+}
+
+impl Div<f32> for Vec3 {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self {
+        Self { x: self.x / rhs, y: self.y / rhs, z: self.z / rhs }
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { x: -self.x, y: -self.y, z: -self.z }
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self.z += rhs.z;
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+        self.z -= rhs.z;
+    }
+}
+
+// even-odd rule, closed polygon of any winding, false with less than 3 points
+pub(crate) fn polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+
+    inside && points.len() >= 3
+}
+
+pub(crate) fn closest_on_segment(a: Vec2, b: Vec2, point: Vec2) -> Vec2 {
+    let edge = b - a;
+    let length = edge.dot(edge);
+
+    if length <= 0.0 {
+        return a;
+    }
+
+    let t = ((point - a).dot(edge) / length).clamp(0.0, 1.0);
+    a + edge * t
+}
@@ -0,0 +1,129 @@
+use crate::Vec2;
+use crate::math::{closest_on_segment, polygon_contains};
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Obstacle {
+    Circle { center: Vec2, radius: f32 },
+    Rect { min: Vec2, max: Vec2 }, // axis-aligned
+    Polygon { points: Vec<Vec2> }, // closed, any winding
+    Segment { a: Vec2, b: Vec2 },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Hit {
+    pub distance: f32, // 0.0 when the origin is already inside
+    pub normal: Vec2, // pointing away from the obstacle, towards the origin side
+}
+
+impl Obstacle {
+    // dir must be normalized
+    pub fn raycast(&self, origin: Vec2, dir: Vec2, max_distance: f32) -> Option<Hit> {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                let offset = origin - *center;
+
+                if offset.magnitude() <= *radius {
+                    return Some(Hit { distance: 0.0, normal: offset.normalize() });
+                }
+
+                // |offset + dir * t| = radius
+                let b = offset.dot(dir);
+                let c = offset.dot(offset) - radius * radius;
+                let discriminant = b * b - c;
+
+                if discriminant < 0.0 {
+                    return None;
+                }
+
+                let t = -b - discriminant.sqrt();
+
+                if t < 0.0 || t > max_distance {
+                    return None;
+                }
+
+                let point = origin + dir * t;
+                Some(Hit { distance: t, normal: (point - *center).normalize() })
+            }
+
+            _ => {
+                let edges = self.edges();
+
+                if self.contains(origin) {
+                    // escape through the closest edge
+                    let closest = edges
+                        .iter()
+                        .map(|(a, b)| closest_on_segment(*a, *b, origin))
+                        .min_by(|p, q| (*p - origin).magnitude().total_cmp(&(*q - origin).magnitude()))?;
+
+                    return Some(Hit { distance: 0.0, normal: (closest - origin).normalize() });
+                }
+
+                edges
+                    .iter()
+                    .filter_map(|(a, b)| Obstacle::raycast_segment(*a, *b, origin, dir, max_distance))
+                    .min_by(|h1, h2| h1.distance.total_cmp(&h2.distance))
+            }
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Obstacle::Circle { center, radius } => (point - *center).magnitude() <= *radius,
+
+            Obstacle::Rect { min, max } => {
+                point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+            }
+
+            Obstacle::Polygon { points } => polygon_contains(points, point),
+
+            Obstacle::Segment { .. } => false,
+        }
+    }
+
+    fn edges(&self) -> Vec<(Vec2, Vec2)> {
+        match self {
+            Obstacle::Circle { .. } => Vec::new(),
+
+            Obstacle::Rect { min, max } => {
+                let tr = Vec2::new(max.x, min.y);
+                let bl = Vec2::new(min.x, max.y);
+
+                vec![(*min, tr), (tr, *max), (*max, bl), (bl, *min)]
+            }
+
+            Obstacle::Polygon { points } => {
+                (0..points.len())
+                    .map(|i| (points[i], points[(i + 1) % points.len()]))
+                    .collect()
+            }
+
+            Obstacle::Segment { a, b } => vec![(*a, *b)],
+        }
+    }
+
+    fn raycast_segment(a: Vec2, b: Vec2, origin: Vec2, dir: Vec2, max_distance: f32) -> Option<Hit> {
+        let ray = dir * max_distance;
+        let edge = b - a;
+        let denominator = ray.cross(edge);
+
+        // parallel
+        if denominator.abs() < 1e-6 {
+            return None;
+        }
+
+        let t = (a - origin).cross(edge) / denominator;
+        let u = (a - origin).cross(ray) / denominator;
+
+        if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let mut normal = edge.perp().normalize();
+        if normal.dot(origin - a) < 0.0 {
+            normal = -normal;
+        }
+
+        Some(Hit { distance: t * max_distance, normal })
+    }
+}
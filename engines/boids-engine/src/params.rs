@@ -1,15 +1,94 @@
+use std::f32::consts::{PI, TAU};
+use std::fmt;
+
+use crate::Boundary;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NeighborMode {
+    Metric, // every boid within perception_radius
+    Topological, // the k_neighbors closest boids, at any distance
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "lowercase"))]
+pub enum ParamKind {
+    Float,
+    Integer,
+    Bool, // 0.0 or 1.0
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParamDescriptor {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    pub description: &'static str,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamError {
+    Unknown(String),
+    NotANumber(&'static str),
+    NotAnInteger(&'static str, f32),
+    OutOfRange { name: &'static str, value: f32, min: f32, max: f32 },
+    UnknownSpecies(usize),
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamError::Unknown(name) => write!(f, "unknown param \"{}\"", name),
+            ParamError::NotANumber(name) => write!(f, "{} must be a number", name),
+            ParamError::NotAnInteger(name, value) => write!(f, "{} must be a whole number, got {}", name, value),
+            ParamError::OutOfRange { name, value, min, max } => {
+                write!(f, "{} must be between {} and {}, got {}", name, min, max, value)
+            }
+            ParamError::UnknownSpecies(species) => write!(f, "unknown species {}", species),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Params {
     pub separation: f32,
     pub alignment: f32,
     pub cohesion: f32,
     pub attraction: f32, // aditional 4th rule, external force
-    pub noise: f32, // noise factor
+    pub avoidance: f32, // steering around obstacles
+    pub flee: f32, // steering away from predators
+    pub follow: f32, // keeping a leader formation slot
+    pub flow: f32, // steering along the world flow field
+    pub noise: f32, // random acceleration, px/s^2
+
+    pub mass: f32, // acceleration = steering force / mass
+    pub max_force: f32, // steering force limit, px/s^2 at mass 1.0
 
     pub max_speed: f32,
-    pub perception_radius: f32,
+    pub min_speed: f32,
+    pub max_turn_rate: f32, // radians per second
+    pub perception_radius: f32, // alignment and cohesion
+    pub separation_radius: f32,
+    pub view_angle: f32, // radians, full cone centered on the heading, TAU = no blind spot
+    pub distance_weighting: bool, // closer neighbours count more for alignment and cohesion
+    pub neighbor_mode: NeighborMode,
+    pub k_neighbors: usize,
+    pub look_ahead: f32, // obstacle raycast distance
+    pub flee_radius: f32,
+    pub arrival_radius: f32, // followers slow down within this distance of their slot
+    pub flow_resolution: f32, // px between flow field samples, 0.0 = continuous
 
-    pub bounce_on_edge: bool, 
+    pub predator_speed: f32,
+    pub predator_steer: f32, // chase acceleration towards the nearest boid
+    pub capture_radius: f32, // 0.0 disables captures
+
+    pub boundary: Boundary,
 }
 
 impl Default for Params {
@@ -19,11 +98,200 @@ impl Default for Params {
             alignment: 0.5,
             cohesion: 3.0,
             attraction: 15.0,
-            noise: 0.5,
+            avoidance: 10.0,
+            flee: 20.0,
+            follow: 10.0,
+            flow: 5.0,
+            noise: 30.0,
+
+            mass: 1.0,
+            max_force: 600.0,
 
             max_speed: 200.0,
+            min_speed: 40.0,
+            max_turn_rate: 2.0 * PI,
             perception_radius: 50.0,
-            bounce_on_edge: true,
+            separation_radius: 25.0,
+            view_angle: 1.5 * PI,
+            distance_weighting: false,
+            neighbor_mode: NeighborMode::Metric,
+            k_neighbors: 7,
+            look_ahead: 60.0,
+            flee_radius: 80.0,
+            arrival_radius: 50.0,
+            flow_resolution: 0.0,
+
+            predator_speed: 180.0,
+            predator_steer: 480.0,
+            capture_radius: 0.0,
+            boundary: Boundary::Bounce,
         }
     }
-}
\ No newline at end of file
+}
+
+impl Params {
+    // every param settable by name, defaults taken from Params::default.
+    // neighbor_mode and boundary aren't numbers, they only have their own World setters
+    pub fn descriptors() -> Vec<ParamDescriptor> {
+        use ParamKind::*;
+
+        let d = Params::default();
+
+        let float = |name, min, max, default, description| {
+            ParamDescriptor { name, kind: Float, min, max, default, description }
+        };
+
+        vec![
+            float("separation", 0.0, 100.0, d.separation, "Weight of steering away from close neighbours"),
+            float("alignment", 0.0, 100.0, d.alignment, "Weight of matching the neighbours heading"),
+            float("cohesion", 0.0, 100.0, d.cohesion, "Weight of steering towards the neighbours center"),
+            float("attraction", 0.0, 100.0, d.attraction, "Weight of attractors and repellers"),
+            float("avoidance", 0.0, 100.0, d.avoidance, "Weight of steering around obstacles"),
+            float("flee", 0.0, 100.0, d.flee, "Weight of steering away from predators"),
+            float("follow", 0.0, 100.0, d.follow, "Weight of keeping a leader formation slot"),
+            float("flow", 0.0, 100.0, d.flow, "Weight of steering along the flow field"),
+            float("noise", 0.0, 1000.0, d.noise, "Random acceleration, px/s^2"),
+            float("mass", 0.01, 100.0, d.mass, "Divides the steering force"),
+            float("max_force", 0.0, 10000.0, d.max_force, "Steering force limit, px/s^2 at mass 1"),
+            float("max_speed", 0.0, 2000.0, d.max_speed, "Speed limit, px/s"),
+            float("min_speed", 0.0, 2000.0, d.min_speed, "Boids never go slower, px/s"),
+            float("max_turn_rate", 0.0, 100.0, d.max_turn_rate, "Heading change limit, radians per second"),
+            float("perception_radius", 0.0, 1000.0, d.perception_radius, "Alignment and cohesion reach, px"),
+            float("separation_radius", 0.0, 1000.0, d.separation_radius, "Separation reach, px"),
+            float("view_angle", 0.0, TAU, d.view_angle, "Field of view centered on the heading, radians"),
+            ParamDescriptor {
+                name: "distance_weighting",
+                kind: Bool,
+                min: 0.0,
+                max: 1.0,
+                default: d.distance_weighting as u8 as f32,
+                description: "Closer neighbours count more for alignment and cohesion",
+            },
+            ParamDescriptor {
+                name: "k_neighbors",
+                kind: Integer,
+                min: 0.0,
+                max: 64.0,
+                default: d.k_neighbors as f32,
+                description: "Neighbours perceived in topological mode",
+            },
+            float("look_ahead", 0.0, 1000.0, d.look_ahead, "Obstacle raycast distance, px"),
+            float("flee_radius", 0.0, 1000.0, d.flee_radius, "Distance at which boids notice predators, px"),
+            float("arrival_radius", 0.0, 1000.0, d.arrival_radius, "Followers slow down within this distance of their slot, px"),
+            float("flow_resolution", 0.0, 1000.0, d.flow_resolution, "Distance between flow field samples, 0 = continuous, px"),
+            float("predator_speed", 0.0, 2000.0, d.predator_speed, "Predator speed limit, px/s"),
+            float("predator_steer", 0.0, 10000.0, d.predator_steer, "Predator chase acceleration, px/s^2"),
+            float("capture_radius", 0.0, 1000.0, d.capture_radius, "Predator capture distance, 0 disables captures"),
+        ]
+    }
+
+    pub fn descriptor(name: &str) -> Option<ParamDescriptor> {
+        Params::descriptors().into_iter().find(|descriptor| descriptor.name == name)
+    }
+
+    pub fn get(&self, name: &str) -> Result<f32, ParamError> {
+        let value = match name {
+            "separation" => self.separation,
+            "alignment" => self.alignment,
+            "cohesion" => self.cohesion,
+            "attraction" => self.attraction,
+            "avoidance" => self.avoidance,
+            "flee" => self.flee,
+            "follow" => self.follow,
+            "flow" => self.flow,
+            "noise" => self.noise,
+            "mass" => self.mass,
+            "max_force" => self.max_force,
+            "max_speed" => self.max_speed,
+            "min_speed" => self.min_speed,
+            "max_turn_rate" => self.max_turn_rate,
+            "perception_radius" => self.perception_radius,
+            "separation_radius" => self.separation_radius,
+            "view_angle" => self.view_angle,
+            "distance_weighting" => self.distance_weighting as u8 as f32,
+            "k_neighbors" => self.k_neighbors as f32,
+            "look_ahead" => self.look_ahead,
+            "flee_radius" => self.flee_radius,
+            "arrival_radius" => self.arrival_radius,
+            "flow_resolution" => self.flow_resolution,
+            "predator_speed" => self.predator_speed,
+            "predator_steer" => self.predator_steer,
+            "capture_radius" => self.capture_radius,
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        };
+
+        Ok(value)
+    }
+
+    // validated against the descriptor and the related params, params are left untouched on error
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), ParamError> {
+        let name = self.check(name, value)?;
+
+        match name {
+            "separation" => self.separation = value,
+            "alignment" => self.alignment = value,
+            "cohesion" => self.cohesion = value,
+            "attraction" => self.attraction = value,
+            "avoidance" => self.avoidance = value,
+            "flee" => self.flee = value,
+            "follow" => self.follow = value,
+            "flow" => self.flow = value,
+            "noise" => self.noise = value,
+            "mass" => self.mass = value,
+            "max_force" => self.max_force = value,
+            "max_speed" => self.max_speed = value,
+            "min_speed" => self.min_speed = value,
+            "max_turn_rate" => self.max_turn_rate = value,
+            "perception_radius" => self.perception_radius = value,
+            "separation_radius" => self.separation_radius = value,
+            "view_angle" => self.view_angle = value,
+            "distance_weighting" => self.distance_weighting = value != 0.0,
+            "k_neighbors" => self.k_neighbors = value as usize,
+            "look_ahead" => self.look_ahead = value,
+            "flee_radius" => self.flee_radius = value,
+            "arrival_radius" => self.arrival_radius = value,
+            "flow_resolution" => self.flow_resolution = value,
+            "predator_speed" => self.predator_speed = value,
+            "predator_steer" => self.predator_steer = value,
+            "capture_radius" => self.capture_radius = value,
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    // every param within its range, e.g. for params that didn't go through set
+    pub fn validate(&self) -> Result<(), ParamError> {
+        for descriptor in Params::descriptors() {
+            self.check(descriptor.name, self.get(descriptor.name)?)?;
+        }
+
+        Ok(())
+    }
+
+    // min_speed can't go above max_speed, nor max_speed below min_speed
+    fn check(&self, name: &str, value: f32) -> Result<&'static str, ParamError> {
+        let descriptor = Params::descriptor(name).ok_or_else(|| ParamError::Unknown(name.to_string()))?;
+        let name = descriptor.name;
+
+        if value.is_nan() {
+            return Err(ParamError::NotANumber(name));
+        }
+
+        if descriptor.kind != ParamKind::Float && value.fract() != 0.0 {
+            return Err(ParamError::NotAnInteger(name, value));
+        }
+
+        let (min, max) = match name {
+            "min_speed" => (descriptor.min, descriptor.max.min(self.max_speed)),
+            "max_speed" => (descriptor.min.max(self.min_speed), descriptor.max),
+            _ => (descriptor.min, descriptor.max),
+        };
+
+        if value < min || value > max {
+            return Err(ParamError::OutOfRange { name, value, min, max });
+        }
+
+        Ok(name)
+    }
+}
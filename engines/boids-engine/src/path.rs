@@ -0,0 +1,149 @@
+use crate::Vec2;
+
+/*
+ * references:
+ *
+ * Catmull-Rom splines:
+ * https://en.wikipedia.org/wiki/Centripetal_Catmull%E2%80%93Rom_spline
+ */
+
+/*
+ * note:
+ * the path is sampled once into a polyline with cumulative lengths,
+ * so positions are looked up by distance travelled instead of by curve parameter
+ */
+
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PathKind {
+    Waypoints, // straight segments
+    Spline, // uniform Catmull-Rom through every point
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Path {
+    points: Vec<Vec2>,
+    looped: bool, // back to the first point, otherwise it stops at the last one
+    samples: Vec<Vec2>,
+    lengths: Vec<f32>, // cumulative, lengths[i] = distance from samples[0] to samples[i]
+}
+
+impl Path {
+    pub fn new(points: Vec<Vec2>, kind: PathKind, looped: bool) -> Self {
+        assert!(points.len() >= 2, "Error: a path needs at least 2 points");
+
+        let samples = match kind {
+            PathKind::Waypoints => {
+                let mut samples = points.clone();
+                if looped {
+                    samples.push(points[0]);
+                }
+                samples
+            }
+
+            PathKind::Spline => Path::sample_spline(&points, looped),
+        };
+
+        let mut lengths = Vec::with_capacity(samples.len());
+        let mut total = 0.0;
+
+        for i in 0..samples.len() {
+            if i > 0 {
+                total += (samples[i] - samples[i - 1]).magnitude();
+            }
+            lengths.push(total);
+        }
+
+        Self { points, looped, samples, lengths }
+    }
+
+    // what new guarantees, e.g. for restored snapshots
+    pub fn is_valid(&self) -> bool {
+        self.points.len() >= 2 && self.samples.len() >= 2 && self.lengths.len() == self.samples.len()
+    }
+
+    pub fn get_points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    pub fn is_looped(&self) -> bool {
+        self.looped
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    // wraps around looped paths, clamps to the ends otherwise
+    pub fn point_at(&self, distance: f32) -> Vec2 {
+        let (i, t) = self.locate(distance);
+        self.samples[i] + (self.samples[i + 1] - self.samples[i]) * t
+    }
+
+    // unit direction of travel at distance
+    pub fn tangent_at(&self, distance: f32) -> Vec2 {
+        let (i, _) = self.locate(distance);
+        (self.samples[i + 1] - self.samples[i]).normalize()
+    }
+
+    // (segment index, fraction along it)
+    fn locate(&self, distance: f32) -> (usize, f32) {
+        let length = self.length();
+
+        let distance = if self.looped && length > 0.0 {
+            distance.rem_euclid(length)
+        } else {
+            distance.clamp(0.0, length)
+        };
+
+        let i = self.lengths.partition_point(|l| *l <= distance).clamp(1, self.samples.len() - 1) - 1;
+        let segment = self.lengths[i + 1] - self.lengths[i];
+
+        let t = if segment > 0.0 { (distance - self.lengths[i]) / segment } else { 0.0 };
+
+        (i, t.clamp(0.0, 1.0))
+    }
+
+    fn sample_spline(points: &[Vec2], looped: bool) -> Vec<Vec2> {
+        let n = points.len();
+        let segments = if looped { n } else { n - 1 };
+
+        // open paths repeat their end points as the outer controls
+        let control = |i: isize| {
+            if looped {
+                points[i.rem_euclid(n as isize) as usize]
+            } else {
+                points[i.clamp(0, n as isize - 1) as usize]
+            }
+        };
+
+        let mut samples = Vec::with_capacity(segments * SAMPLES_PER_SEGMENT + 1);
+
+        for segment in 0..segments as isize {
+            let p0 = control(segment - 1);
+            let p1 = control(segment);
+            let p2 = control(segment + 1);
+            let p3 = control(segment + 2);
+
+            for step in 0..SAMPLES_PER_SEGMENT {
+                let t = step as f32 / SAMPLES_PER_SEGMENT as f32;
+                let t2 = t * t;
+                let t3 = t2 * t;
+
+                samples.push(
+                    (p1 * 2.0
+                        + (p2 - p0) * t
+                        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+                        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+                        * 0.5,
+                );
+            }
+        }
+
+        samples.push(control(segments as isize));
+        samples
+    }
+}
@@ -0,0 +1,26 @@
+use crate::Vec2;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Predator {
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub prev_pos: Vec2, // before the last step, for render interpolation
+}
+
+impl Predator {
+    pub fn new(pos: Vec2, vel: Vec2) -> Self {
+        Self { pos, vel, prev_pos: pos }
+    }
+
+    pub fn interpolate(&self, alpha: f32) -> Vec2 {
+        self.prev_pos + (self.pos - self.prev_pos) * alpha
+    }
+}
+
+// predator got within capture_radius of a boid, the boid respawns elsewhere
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capture {
+    pub predator: usize,
+    pub boid: usize,
+}
\ No newline at end of file
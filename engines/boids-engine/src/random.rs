@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 
 #[cfg(target_arch = "wasm32")]
 pub fn random_f32() -> f32 {
@@ -10,3 +11,51 @@ pub fn random_f32() -> f32 {
     rand::thread_rng().r#gen()
 }
 
+
+
+/*
+ * note:
+ * seedable generator owned by World, so a snapshot restores the exact same sequence.
+ * the state is atomic so it can be drawn from behind &self, e.g. while computing accelerations,
+ * and World stays Send + Sync. draws only happen on one thread at a time, Relaxed is enough
+ *
+ * reference:
+ * https://prng.di.unimi.it/splitmix64.c
+ */
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rng {
+    state: AtomicU64,
+}
+
+impl Clone for Rng {
+    fn clone(&self) -> Self {
+        Self::new(self.state.load(Ordering::Relaxed))
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: AtomicU64::new(seed) }
+    }
+
+    pub fn from_entropy() -> Self {
+        let bits = |shift: u32| ((random_f32() * (1 << 24) as f32) as u64) << shift;
+
+        Self::new(bits(0) | bits(24) | bits(48))
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed).wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // in [0, 1)
+    pub fn next_f32(&self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
@@ -0,0 +1,114 @@
+use crate::Boid;
+use crate::Vec2;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Neighbor {
+    pub index: usize, // into World::get_boids
+    pub dist: f32,
+}
+
+/*
+ * note:
+ * uniform grid rebuilt every step, positions outside of the world
+ * are clamped into the border cells
+ */
+pub struct SpatialGrid {
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    points: Vec<Vec2>,
+}
+
+impl SpatialGrid {
+    pub fn new(boids: &[Boid], width: f32, height: f32, cell_size: f32) -> Self {
+        let cell_size = cell_size.max(1.0);
+        let cols = ((width / cell_size).ceil() as usize).max(1);
+        let rows = ((height / cell_size).ceil() as usize).max(1);
+
+        let mut grid = Self {
+            cell_size,
+            cols,
+            rows,
+            cells: vec![Vec::new(); cols * rows],
+            points: boids.iter().map(|boid| boid.pos).collect(),
+        };
+
+        for (i, pos) in grid.points.iter().enumerate() {
+            let (x, y) = grid.cell_of(*pos);
+            grid.cells[x + y * cols].push(i);
+        }
+
+        grid
+    }
+
+    // calls f(index, dist) for every point closer than radius
+    pub fn within(&self, pos: Vec2, radius: f32, mut f: impl FnMut(usize, f32)) {
+        let (cx, cy) = self.cell_of(pos);
+        let reach = (radius / self.cell_size).ceil() as usize;
+
+        for y in cy.saturating_sub(reach)..=(cy + reach).min(self.rows - 1) {
+            for x in cx.saturating_sub(reach)..=(cx + reach).min(self.cols - 1) {
+                for &i in &self.cells[x + y * self.cols] {
+                    let dist = (self.points[i] - pos).magnitude();
+
+                    if dist < radius {
+                        f(i, dist);
+                    }
+                }
+            }
+        }
+    }
+
+    // k closest accepted points, sorted by distance, searching rings of cells outwards
+    pub fn k_nearest(&self, pos: Vec2, k: usize, mut accept: impl FnMut(usize) -> bool) -> Vec<Neighbor> {
+        let mut nearest: Vec<Neighbor> = Vec::with_capacity(k + 1);
+
+        if k == 0 {
+            return nearest;
+        }
+
+        let (cx, cy) = self.cell_of(pos);
+        let max_ring = self.cols.max(self.rows);
+
+        for ring in 0..=max_ring {
+            // every point in this ring is at least this far away
+            let min_dist = ring.saturating_sub(1) as f32 * self.cell_size;
+
+            if nearest.len() == k && nearest[k - 1].dist <= min_dist {
+                break;
+            }
+
+            for y in cy.saturating_sub(ring)..=(cy + ring).min(self.rows - 1) {
+                for x in cx.saturating_sub(ring)..=(cx + ring).min(self.cols - 1) {
+                    if x.abs_diff(cx).max(y.abs_diff(cy)) != ring {
+                        continue;
+                    }
+
+                    for &i in &self.cells[x + y * self.cols] {
+                        if !accept(i) {
+                            continue;
+                        }
+
+                        let dist = (self.points[i] - pos).magnitude();
+                        let at = nearest.partition_point(|n| n.dist <= dist);
+
+                        if at < k {
+                            nearest.insert(at, Neighbor { index: i, dist });
+                            nearest.truncate(k);
+                        }
+                    }
+                }
+            }
+        }
+
+        nearest
+    }
+
+    fn cell_of(&self, pos: Vec2) -> (usize, usize) {
+        let x = (pos.x / self.cell_size).floor().max(0.0) as usize;
+        let y = (pos.y / self.cell_size).floor().max(0.0) as usize;
+
+        (x.min(self.cols - 1), y.min(self.rows - 1))
+    }
+}
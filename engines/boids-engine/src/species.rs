@@ -0,0 +1,8 @@
+// how boids of one species react to boids of another
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interaction {
+    Flock, // separation, alignment and cohesion
+    Separate, // separation only
+    Ignore,
+}
\ No newline at end of file
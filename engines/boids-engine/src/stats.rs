@@ -0,0 +1,133 @@
+use crate::Boid;
+use crate::Vec2;
+
+/*
+ * references:
+ *
+ * order parameters (polarization, milling):
+ * https://doi.org/10.1006/jtbi.2002.3065 (Couzin et al., collective memory and spatial sorting)
+ */
+
+/*
+ * note:
+ * groups are the connected components of the perceived neighbour graph, edges taken both ways.
+ * positions are not unwrapped, so a group crossing a wrap-around seam has its centroid in between
+ */
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Group {
+    pub size: usize,
+    pub centroid: Vec2,
+    pub mean_velocity: Vec2,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlockStats {
+    pub groups: Vec<Group>, // largest first
+    pub labels: Vec<usize>, // group index per boid
+    pub polarization: f32, // |mean heading|, 1.0 = all boids aligned
+    pub angular_momentum: f32, // milling, 1.0 = all boids circling the centroid the same way
+    pub mean_nearest_distance: f32, // 0.0 with less than two boids
+}
+
+impl FlockStats {
+    // neighbors[i] are the boids perceived by boid i, nearest[i] the distance to its closest boid
+    pub fn new(boids: &[Boid], neighbors: &[Vec<usize>], nearest: &[Option<f32>]) -> Self {
+        let n = boids.len();
+
+        // union-find over neighbour pairs
+        let mut parent: Vec<usize> = (0..n).collect();
+
+        let find = |parent: &mut Vec<usize>, mut i: usize| {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        };
+
+        for (i, list) in neighbors.iter().enumerate() {
+            for &j in list {
+                let a = find(&mut parent, i);
+                let b = find(&mut parent, j);
+
+                if a != b {
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        // (root, size, position sum, velocity sum)
+        let mut sums: Vec<(usize, usize, Vec2, Vec2)> = Vec::new();
+        let mut root_group = vec![usize::MAX; n];
+        let mut labels = vec![0; n];
+
+        for (i, boid) in boids.iter().enumerate() {
+            let root = find(&mut parent, i);
+
+            if root_group[root] == usize::MAX {
+                root_group[root] = sums.len();
+                sums.push((root, 0, Vec2::ZERO, Vec2::ZERO));
+            }
+
+            let group = &mut sums[root_group[root]];
+            group.1 += 1;
+            group.2 += boid.pos;
+            group.3 += boid.vel;
+        }
+
+        sums.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        for (index, (root, ..)) in sums.iter().enumerate() {
+            root_group[*root] = index;
+        }
+
+        for (i, label) in labels.iter_mut().enumerate() {
+            *label = root_group[find(&mut parent, i)];
+        }
+
+        let groups = sums
+            .iter()
+            .map(|&(_, size, pos, vel)| Group {
+                size,
+                centroid: pos / size as f32,
+                mean_velocity: vel / size as f32,
+            })
+            .collect();
+
+        let (polarization, angular_momentum) = FlockStats::order(boids);
+
+        let distances: Vec<f32> = nearest.iter().flatten().copied().collect();
+        let mean_nearest_distance = if distances.is_empty() {
+            0.0
+        } else {
+            distances.iter().sum::<f32>() / distances.len() as f32
+        };
+
+        FlockStats { groups, labels, polarization, angular_momentum, mean_nearest_distance }
+    }
+
+    // (polarization, angular momentum) over the whole flock, from unit headings
+    fn order(boids: &[Boid]) -> (f32, f32) {
+        if boids.is_empty() {
+            return (0.0, 0.0);
+        }
+
+        let n = boids.len() as f32;
+        let centroid = boids.iter().fold(Vec2::ZERO, |sum, boid| sum + boid.pos) / n;
+
+        let mut heading = Vec2::ZERO;
+        let mut rotation = 0.0;
+
+        for boid in boids {
+            let dir = boid.vel.normalize();
+
+            heading += dir;
+            rotation += (boid.pos - centroid).normalize().cross(dir);
+        }
+
+        (heading.magnitude() / n, rotation.abs() / n)
+    }
+}
@@ -1,27 +1,86 @@
-use crate::Params;
+use crate::{NeighborMode, ParamError, Params};
 use crate::Boid;
 use crate::Vec2;
-use crate::random::random_f32;
+use crate::Obstacle;
+use crate::{Capture, Predator};
+use crate::Interaction;
+use crate::ForceField;
+use crate::{Neighbor, SpatialGrid};
+use crate::{Alignment, Attraction, Avoidance, Behavior, Cohesion, Flee, FollowFlow, FollowLeader, Separation};
+use crate::FlowField;
+use crate::{Boundary, Region};
+use crate::{Formation, Leader, Path};
+use crate::Integrator;
+use crate::FlockStats;
+use crate::buffer::write_buffer;
+use crate::random::Rng;
+#[cfg(feature = "serde")]
+use crate::SnapshotError;
 
+use std::f32::consts::{PI, TAU};
+
+// captures kept for take_captures, the oldest are dropped when nobody polls them
+const MAX_CAPTURES: usize = 1024;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct World {
     boids: Vec<Boid>,
-    params: Params,
+    species: Vec<Params>, // per species, species 0 also drives predators
+    interactions: Vec<Interaction>, // species x species, row reacts to column
     width: f32,
     height: f32,
 
-    attractor: Option<Vec2>,
+    fields: Vec<(usize, ForceField)>, // (id, field)
+    next_field_id: usize,
+    attractor: Option<usize>, // field id driven by set_attractor
     boost_on_attractor_exit: bool,
+
+    obstacles: Vec<Obstacle>,
+
+    predators: Vec<Predator>,
+    captures: Vec<Capture>, // since last take_captures, at most MAX_CAPTURES
+
+    leaders: Vec<Leader>,
+
+    flow_field: Option<FlowField>,
+
+    containment: Option<Region>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    behaviors: Vec<(Box<dyn Behavior>, f32)>, // custom behaviors and their weights, not serialized
+
+    integrator: Integrator,
+    fixed_dt: f32,
+    max_substeps: usize, // per advance, drops time instead of spiralling
+    accumulator: f32, // real time not yet simulated
+    half_kick: bool, // Verlet left velocities at t + dt/2, the next step finishes the kick
+
+    rng: Rng,
+
+    trail_length: usize, // positions kept per boid, 0 disables trails
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    buffer: Vec<f32>, // interleaved boid data, see BUFFER_LAYOUT
 }
 
 impl World {
     pub fn new(n: usize, width: f32, height: f32) -> Self {
+        World::with_rng(n, width, height, Rng::from_entropy())
+    }
+
+    // same seed, same flock
+    pub fn with_seed(n: usize, width: f32, height: f32, seed: u64) -> Self {
+        World::with_rng(n, width, height, Rng::new(seed))
+    }
+
+    fn with_rng(n: usize, width: f32, height: f32, rng: Rng) -> Self {
         let mut boids = Vec::with_capacity(n);
 
         for _ in 0..n {
             boids.push(Boid::new(
                 Vec2::new(
-                    random_f32() * width,
-                    random_f32() * height
+                    rng.next_f32() * width,
+                    rng.next_f32() * height
                 ),
                 Vec2::new(
                     0.2,
@@ -30,211 +89,1196 @@ impl World {
             ));
         }
 
-        Self {
+        let mut world = Self {
             boids,
-            params: Params::default(),
+            species: vec![Params::default()],
+            interactions: vec![Interaction::Flock],
             width,
             height,
+            fields: Vec::new(),
+            next_field_id: 0,
             attractor: None,
             boost_on_attractor_exit: false,
-        }
+            obstacles: Vec::new(),
+            predators: Vec::new(),
+            captures: Vec::new(),
+
+            leaders: Vec::new(),
+
+            flow_field: None,
+
+            containment: None,
+            behaviors: Vec::new(),
+
+            integrator: Integrator::SemiImplicitEuler,
+            fixed_dt: 1.0 / 60.0,
+            max_substeps: 8,
+            accumulator: 0.0,
+            half_kick: false,
+
+            rng,
+
+            trail_length: 0,
+
+            buffer: Vec::new(),
+        };
+
+        world.sync_buffer();
+        world
+    }
+
+    pub fn set_params(&mut self, name: &str, value: f32) -> Result<(), ParamError> {
+        self.set_species_params(0, name, value)
     }
 
-    pub fn set_params(&mut self, name: &str, value: f32) {
-        match name {
-            "separation" => self.params.separation = value,
-            "alignment" => self.params.alignment = value,
-            "cohesion" => self.params.cohesion = value,
-            "attraction" => self.params.attraction = value,
-            "noise" => self.params.noise = value,
-            "max_speed" => self.params.max_speed = value,
-            "perception_radius" => self.params.perception_radius = value,
-            _ => (),
+    pub fn set_species_params(&mut self, species: usize, name: &str, value: f32) -> Result<(), ParamError> {
+        self.species
+            .get_mut(species)
+            .ok_or(ParamError::UnknownSpecies(species))?
+            .set(name, value)
+    }
+
+    pub fn get_param(&self, name: &str) -> Result<f32, ParamError> {
+        self.get_species_param(0, name)
+    }
+
+    pub fn get_species_param(&self, species: usize, name: &str) -> Result<f32, ParamError> {
+        self.species
+            .get(species)
+            .ok_or(ParamError::UnknownSpecies(species))?
+            .get(name)
+    }
+
+    pub fn get_species_params(&self, species: usize) -> &Params {
+        &self.species[species]
+    }
+
+    pub fn species_count(&self) -> usize {
+        self.species.len()
+    }
+
+    // spawns n boids of the new species, which only separates from the existing ones
+    pub fn add_species(&mut self, params: Params, n: usize) -> usize {
+        World::assert_boundary(params.boundary);
+
+        let old = self.species.len();
+        let count = old + 1;
+
+        let mut interactions = vec![Interaction::Separate; count * count];
+        for a in 0..count {
+            for b in 0..count {
+                interactions[a * count + b] = if a < old && b < old {
+                    self.interactions[a * old + b]
+                } else if a == b {
+                    Interaction::Flock
+                } else {
+                    Interaction::Separate
+                };
+            }
         }
+
+        self.species.push(params);
+        self.interactions = interactions;
+
+        for _ in 0..n {
+            self.boids.push(Boid::with_species(
+                Vec2::new(
+                    self.rng.next_f32() * self.width,
+                    self.rng.next_f32() * self.height
+                ),
+                Vec2::new(
+                    0.2,
+                    0.2
+                ),
+                old,
+            ));
+        }
+
+        self.sync_buffer();
+        old
     }
 
+    // how boids of species a react to boids of species b
+    pub fn set_interaction(&mut self, a: usize, b: usize, interaction: Interaction) {
+        let count = self.species.len();
+        assert!(a < count && b < count, "Error: species must be between 0 and species_count - 1");
+
+        self.interactions[a * count + b] = interaction;
+    }
+
+    pub fn get_interaction(&self, a: usize, b: usize) -> Interaction {
+        self.interactions[a * self.species.len() + b]
+    }
+
+    // bounce or wrap, kept for the original bool setting
     pub fn set_bounce_on_edge(&mut self, bounce: bool) {
-        self.params.bounce_on_edge = bounce;
+        self.set_boundary(if bounce { Boundary::Bounce } else { Boundary::Wrap });
+    }
+
+    // all species
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        World::assert_boundary(boundary);
+
+        for params in self.species.iter_mut() {
+            params.boundary = boundary;
+        }
+    }
+
+    pub fn set_species_boundary(&mut self, species: usize, boundary: Boundary) {
+        assert!(species < self.species.len(), "Error: species must be between 0 and species_count - 1");
+        World::assert_boundary(boundary);
+
+        self.species[species].boundary = boundary;
+    }
+
+    fn assert_boundary(boundary: Boundary) {
+        if let Boundary::SoftMargin { margin, force } = boundary {
+            assert!(margin >= 0.0 && force >= 0.0, "Error: soft margin and force must not be negative");
+        }
+    }
+
+    // boids are kept inside the region as well as the world rectangle
+    pub fn set_containment(&mut self, region: Option<Region>) {
+        if let Some(region) = &region {
+            assert!(region.is_valid(), "Error: region must have a positive radius or at least 3 points");
+        }
+
+        self.containment = region;
+    }
+
+    pub fn get_containment(&self) -> Option<&Region> {
+        self.containment.as_ref()
+    }
+
+    pub fn set_neighbor_mode(&mut self, mode: NeighborMode) {
+        for params in self.species.iter_mut() {
+            params.neighbor_mode = mode;
+        }
     }
 
     pub fn get_boids(&self) -> &[Boid] {
         &self.boids
     }
 
+    pub fn get_width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> f32 {
+        self.height
+    }
+
+    // returns its index
+    pub fn add_boid(&mut self, boid: Boid) -> usize {
+        assert!(boid.species < self.species.len(), "Error: species must be between 0 and species_count - 1");
+
+        let leader = boid.leader.map(|(leader, _)| leader);
+
+        if let Some(leader) = leader {
+            assert!(leader < self.leaders.len(), "Error: leader must be between 0 and leaders - 1");
+        }
+
+        self.boids.push(boid);
+
+        if let Some(leader) = leader {
+            self.assign_slots(leader);
+        }
+
+        self.sync_buffer();
+        self.boids.len() - 1
+    }
+
+    // the last boid takes the removed index, pending captures are remapped the same way
+    pub fn remove_boid(&mut self, index: usize) -> Option<Boid> {
+        if index >= self.boids.len() {
+            return None;
+        }
+
+        let last = self.boids.len() - 1;
+        let boid = self.boids.swap_remove(index);
+
+        self.captures.retain(|capture| capture.boid != index);
+
+        for capture in &mut self.captures {
+            if capture.boid == last {
+                capture.boid = index;
+            }
+        }
+
+        // close the gap in the formation
+        if let Some((leader, _)) = boid.leader {
+            self.assign_slots(leader);
+        }
+
+        self.sync_buffer();
+        Some(boid)
+    }
+
+    // count species 0 boids around pos, heading in random directions
+    pub fn spawn_at(&mut self, pos: Vec2, count: usize) {
+        for _ in 0..count {
+            let dir = Vec2::new(self.rng.next_f32() - 0.5, self.rng.next_f32() - 0.5).normalize();
+            let offset = dir * self.rng.next_f32() * 5.0;
+
+            self.boids.push(Boid::new(pos + offset, dir * self.species[0].min_speed));
+        }
+
+        self.sync_buffer();
+    }
+
+    // removes the newest boids, or adds species 0 boids at random positions
+    pub fn set_population(&mut self, n: usize) {
+        if n <= self.boids.len() {
+            let mut leaders: Vec<usize> = self.boids
+                .drain(n..)
+                .filter_map(|boid| boid.leader.map(|(leader, _)| leader))
+                .collect();
+
+            leaders.sort_unstable();
+            leaders.dedup();
+
+            self.captures.retain(|capture| capture.boid < n);
+
+            for leader in leaders {
+                self.assign_slots(leader);
+            }
+        }
+
+        for _ in self.boids.len()..n {
+            self.boids.push(Boid::new(
+                Vec2::new(
+                    self.rng.next_f32() * self.width,
+                    self.rng.next_f32() * self.height
+                ),
+                Vec2::new(
+                    0.2,
+                    0.2
+                )
+            ));
+        }
+
+        self.sync_buffer();
+    }
+
+    // rescale keeps boids and predators at the same relative position, otherwise they are clamped inside
+    pub fn resize(&mut self, width: f32, height: f32, rescale: bool) {
+        assert!(width > 0.0 && height > 0.0, "Error: width and height must be positive");
+
+        let scale = Vec2::new(width / self.width, height / self.height);
+
+        let fit = |pos: Vec2| {
+            if rescale {
+                Vec2::new(pos.x * scale.x, pos.y * scale.y)
+            } else {
+                Vec2::new(pos.x.clamp(0.0, width), pos.y.clamp(0.0, height))
+            }
+        };
+
+        for boid in &mut self.boids {
+            boid.pos = fit(boid.pos);
+            boid.prev_pos = boid.pos;
+            boid.trail.clear();
+        }
+
+        for predator in &mut self.predators {
+            predator.pos = fit(predator.pos);
+            predator.prev_pos = predator.pos;
+        }
+
+        self.width = width;
+        self.height = height;
+
+        self.sync_buffer();
+    }
+
     pub fn set_attractor(&mut self, pos: Option<Vec2>) {
-        self.attractor = pos;
+        match (pos, self.attractor) {
+            (Some(pos), Some(id)) => {
+                self.move_field(id, pos);
+            }
+            (Some(pos), None) => {
+                self.attractor = Some(self.add_field(ForceField::attractor(pos)));
+            }
+            (None, Some(id)) => {
+                self.remove_field(id);
+            }
+            (None, None) => (),
+        }
     }
 
     pub fn clear_attractor(&mut self) {
         self.boost_on_attractor_exit = true;
-        self.attractor = None
+        self.set_attractor(None);
+    }
+
+    pub fn add_field(&mut self, field: ForceField) -> usize {
+        let id = self.next_field_id;
+        self.next_field_id += 1;
+
+        self.fields.push((id, field));
+        id
+    }
+
+    pub fn get_field(&self, id: usize) -> Option<&ForceField> {
+        self.fields.iter().find(|(field_id, _)| *field_id == id).map(|(_, field)| field)
+    }
+
+    pub fn get_fields(&self) -> &[(usize, ForceField)] {
+        &self.fields
+    }
+
+    pub fn update_field(&mut self, id: usize, field: ForceField) -> bool {
+        match self.fields.iter_mut().find(|(field_id, _)| *field_id == id) {
+            Some((_, current)) => {
+                *current = field;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn move_field(&mut self, id: usize, pos: Vec2) -> bool {
+        match self.fields.iter_mut().find(|(field_id, _)| *field_id == id) {
+            Some((_, current)) => {
+                current.pos = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_field(&mut self, id: usize) -> bool {
+        if self.attractor == Some(id) {
+            self.attractor = None;
+        }
+
+        let len = self.fields.len();
+        self.fields.retain(|(field_id, _)| *field_id != id);
+        self.fields.len() != len
+    }
+
+    pub fn clear_fields(&mut self) {
+        self.attractor = None;
+        self.fields.clear();
+    }
+
+    pub fn add_obstacle(&mut self, obstacle: Obstacle) -> usize {
+        self.obstacles.push(obstacle);
+        self.obstacles.len() - 1
+    }
+
+    pub fn get_obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.obstacles.clear();
+    }
+
+    pub fn add_predator(&mut self, pos: Vec2) -> usize {
+        self.predators.push(Predator::new(pos, Vec2::ZERO));
+        self.predators.len() - 1
+    }
+
+    pub fn get_predators(&self) -> &[Predator] {
+        &self.predators
+    }
+
+    pub fn clear_predators(&mut self) {
+        self.predators.clear();
+    }
+
+    pub fn take_captures(&mut self) -> Vec<Capture> {
+        std::mem::take(&mut self.captures)
+    }
+
+    pub fn set_flow_field(&mut self, field: FlowField) {
+        self.flow_field = Some(field);
+    }
+
+    pub fn get_flow_field(&self) -> Option<&FlowField> {
+        self.flow_field.as_ref()
+    }
+
+    pub fn clear_flow_field(&mut self) {
+        self.flow_field = None;
+    }
+
+    // leaders start at the beginning of the path, returns its index
+    pub fn add_leader(&mut self, path: Path, speed: f32) -> usize {
+        self.leaders.push(Leader::new(path, speed));
+        self.leaders.len() - 1
+    }
+
+    pub fn get_leaders(&self) -> &[Leader] {
+        &self.leaders
+    }
+
+    pub fn set_formation(&mut self, leader: usize, formation: Formation, spacing: f32) {
+        assert!(leader < self.leaders.len(), "Error: leader must be between 0 and leaders - 1");
+
+        self.leaders[leader].formation = formation;
+        self.leaders[leader].spacing = spacing;
+        self.assign_slots(leader);
+    }
+
+    // boids already following another leader switch to this one
+    pub fn assign_followers(&mut self, leader: usize, boids: &[usize]) {
+        assert!(leader < self.leaders.len(), "Error: leader must be between 0 and leaders - 1");
+
+        for &i in boids {
+            assert!(i < self.boids.len(), "Error: boid must be between 0 and boids - 1");
+
+            if let Some((previous, _)) = self.boids[i].leader {
+                self.boids[i].leader = None;
+                self.assign_slots(previous);
+            }
+
+            self.boids[i].leader = Some((leader, 0));
+        }
+
+        self.assign_slots(leader);
+    }
+
+    // the count boids closest to the leader that don't follow anyone yet
+    pub fn recruit_followers(&mut self, leader: usize, count: usize) {
+        assert!(leader < self.leaders.len(), "Error: leader must be between 0 and leaders - 1");
+
+        let pos = self.leaders[leader].pos;
+        let mut free: Vec<usize> = (0..self.boids.len()).filter(|i| self.boids[*i].leader.is_none()).collect();
+
+        free.sort_by(|a, b| {
+            (self.boids[*a].pos - pos).magnitude().total_cmp(&(self.boids[*b].pos - pos).magnitude())
+        });
+        free.truncate(count);
+
+        self.assign_followers(leader, &free);
+    }
+
+    pub fn release_followers(&mut self, leader: usize) {
+        for boid in &mut self.boids {
+            if boid.leader.is_some_and(|(l, _)| l == leader) {
+                boid.leader = None;
+            }
+        }
+
+        if let Some(leader) = self.leaders.get_mut(leader) {
+            leader.slots = 0;
+        }
+    }
+
+    pub fn clear_leaders(&mut self) {
+        for boid in &mut self.boids {
+            boid.leader = None;
+        }
+
+        self.leaders.clear();
+    }
+
+    // greedy, every slot in order takes the closest follower left
+    fn assign_slots(&mut self, leader: usize) {
+        let mut followers: Vec<usize> = (0..self.boids.len())
+            .filter(|i| self.boids[*i].leader.is_some_and(|(l, _)| l == leader))
+            .collect();
+
+        self.leaders[leader].slots = followers.len();
+
+        for slot in 0..self.leaders[leader].slots {
+            let target = self.leaders[leader].slot_position(slot);
+
+            let closest = (0..followers.len())
+                .min_by(|a, b| {
+                    let da = (self.boids[followers[*a]].pos - target).magnitude();
+                    let db = (self.boids[followers[*b]].pos - target).magnitude();
+                    da.total_cmp(&db)
+                })
+                .unwrap();
+
+            let boid = followers.swap_remove(closest);
+            self.boids[boid].leader = Some((leader, slot));
+        }
+    }
+
+    // runs after the built-in rules, returns its index
+    pub fn add_behavior(&mut self, behavior: Box<dyn Behavior>, weight: f32) -> usize {
+        self.behaviors.push((behavior, weight));
+        self.behaviors.len() - 1
+    }
+
+    pub fn set_behavior_weight(&mut self, index: usize, weight: f32) {
+        assert!(index < self.behaviors.len(), "Error: behavior index out of range");
+
+        self.behaviors[index].1 = weight;
+    }
+
+    pub fn clear_behaviors(&mut self) {
+        self.behaviors.clear();
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    pub fn set_fixed_dt(&mut self, dt: f32) {
+        assert!(dt > 0.0, "Error: fixed dt must be positive");
+
+        self.fixed_dt = dt;
+    }
+
+    // 0 disables trails and clears the existing ones
+    pub fn set_trail_length(&mut self, length: usize) {
+        self.trail_length = length;
+
+        for boid in &mut self.boids {
+            while boid.trail.len() > length {
+                boid.trail.pop_front();
+            }
+        }
+    }
+
+    pub fn get_trail_length(&self) -> usize {
+        self.trail_length
+    }
+
+    // trail of boid i, split where it wrapped around (respawns clear the trail)
+    pub fn get_trail(&self, i: usize) -> Vec<Vec<Vec2>> {
+        let mut polylines: Vec<Vec<Vec2>> = Vec::new();
+        let mut last: Option<Vec2> = None;
+
+        for &pos in &self.boids[i].trail {
+            // no step moves a boid across half of the world
+            let seam = last.is_none_or(|last| {
+                (pos.x - last.x).abs() > self.width * 0.5 || (pos.y - last.y).abs() > self.height * 0.5
+            });
+
+            if seam {
+                polylines.push(Vec::new());
+            }
+
+            if let Some(polyline) = polylines.last_mut() {
+                polyline.push(pos);
+            }
+
+            last = Some(pos);
+        }
+
+        polylines
+    }
+
+    // (boid index, polyline) for every boid
+    pub fn get_trails(&self) -> Vec<(usize, Vec<Vec2>)> {
+        (0..self.boids.len())
+            .flat_map(|i| self.get_trail(i).into_iter().map(move |polyline| (i, polyline)))
+            .collect()
+    }
+
+    pub fn set_max_substeps(&mut self, max_substeps: usize) {
+        self.max_substeps = max_substeps.max(1);
+    }
+
+    // runs as many fixed steps as real_dt allows, returns the interpolation alpha for rendering
+    pub fn advance(&mut self, real_dt: f32) -> f32 {
+        self.accumulator += real_dt.max(0.0);
+
+        let mut substeps = 0;
+
+        while self.accumulator >= self.fixed_dt && substeps < self.max_substeps {
+            self.step(self.fixed_dt);
+            self.accumulator -= self.fixed_dt;
+            substeps += 1;
+        }
+
+        // too far behind (e.g. a background tab), drop the rest
+        if self.accumulator >= self.fixed_dt {
+            self.accumulator %= self.fixed_dt;
+        }
+
+        self.accumulator / self.fixed_dt
     }
 
     pub fn step(&mut self, dt: f32) {
-        let accelerations: Vec<Vec2> = (0..self.boids.len()).map(|i| self.compute_acceleration(i)).collect();
+        for leader in &mut self.leaders {
+            leader.step(dt);
+        }
+
+        let grid = SpatialGrid::new(&self.boids, self.width, self.height, self.cell_size());
+
+        let accelerations: Vec<(Vec2, usize, f32)> = (0..self.boids.len())
+            .map(|i| {
+                let neighbors = self.neighbors(&grid, i);
+                let density = self.density(i, &neighbors);
+
+                (self.compute_acceleration(i, &neighbors), neighbors.len(), density)
+            })
+            .collect();
 
-        for (boid, acc) in self.boids.iter_mut().zip(accelerations) {
-            boid.vel += acc;
+        let bounds = Bounds {
+            width: self.width,
+            height: self.height,
+            region: self.containment.as_ref(),
+            rng: &self.rng,
+        };
 
-            // boost on attractor exit
-            if self.boost_on_attractor_exit {
-                let boost = Vec2::new(
-                    random_f32() - 0.5,
-                    random_f32() - 0.5,
+        for (boid, (force, neighbors, density)) in self.boids.iter_mut().zip(accelerations) {
+            boid.neighbors = neighbors;
+            boid.density = density;
+
+            let params = &self.species[boid.species];
+            let acc = force / params.mass;
+
+            // boost on attractor exit, an instant velocity kick
+            let boost = if self.boost_on_attractor_exit {
+                Vec2::new(
+                    self.rng.next_f32() - 0.5,
+                    self.rng.next_f32() - 0.5,
                 )
                 .normalize()
-                * 3.0;
-                boid.vel += boost;
-            }
-
-            boid.vel = boid.vel.limit(self.params.max_speed);
-            boid.pos += boid.vel * dt;
+                * 3.0
+            } else {
+                Vec2::ZERO
+            };
 
-            if self.params.bounce_on_edge {
-                // bounce
-                if boid.pos.x < 0.0 {
-                    boid.pos.x = 0.0;
-                    boid.vel.x *= -1.0;
-                } else if boid.pos.x > self.width {
-                    boid.pos.x = self.width;
-                    boid.vel.x *= -1.0;
-                }
+            boid.prev_pos = boid.pos;
 
-                if boid.pos.y < 0.0 {
-                    boid.pos.y = 0.0;
-                    boid.vel.y *= -1.0;
-                } else if boid.pos.y > self.height {
-                    boid.pos.y = self.height;
-                    boid.vel.y *= -1.0;
+            match self.integrator {
+                Integrator::Euler => {
+                    boid.pos += boid.vel * dt;
+                    boid.vel = World::limit_velocity(params, &self.rng, boid.vel, boid.vel + acc * dt + boost, dt);
                 }
-            } else {
-                // wrap around
-                if boid.pos.x < 0.0 {
-                    boid.pos.x += self.width;
+                Integrator::SemiImplicitEuler => {
+                    boid.vel = World::limit_velocity(params, &self.rng, boid.vel, boid.vel + acc * dt + boost, dt);
+                    boid.pos += boid.vel * dt;
                 }
+                Integrator::Verlet => {
+                    // second half kick of the last step and first one of this step share a(t)
+                    let kick = acc * (0.5 * dt);
+                    let vel = if self.half_kick { boid.vel + kick } else { boid.vel };
 
-                if boid.pos.y < 0.0 {
-                    boid.pos.y += self.height;
+                    boid.vel = World::limit_velocity(params, &self.rng, boid.vel, vel + kick + boost, dt);
+                    boid.pos += boid.vel * dt;
                 }
+            }
 
-                if boid.pos.x > self.width {
-                    boid.pos.x -= self.width;
-                }
+            boid.acc = acc;
+
+            // a respawned boid starts a new trail
+            if bounds.constrain(params.boundary, &mut boid.pos, &mut boid.vel, &mut boid.prev_pos) {
+                boid.trail.clear();
+            }
 
-                if boid.pos.y > self.height {
-                    boid.pos.y -= self.height;
+            if self.trail_length > 0 {
+                boid.trail.push_back(boid.pos);
+
+                while boid.trail.len() > self.trail_length {
+                    boid.trail.pop_front();
                 }
             }
         }
 
+        self.half_kick = self.integrator == Integrator::Verlet;
+
+        self.step_predators(dt);
+        self.capture();
+
         self.boost_on_attractor_exit = false;
+
+        write_buffer(&self.boids, &mut self.buffer);
     }
 
-    fn compute_acceleration(&self, i: usize) -> Vec2 {
-        let acc = self.separation_rule(i) * self.params.separation
-         + self.alignment_rule(i) * self.params.alignment
-         + self.cohesion_rule(i) * self.params.cohesion
-         + self.attraction_rule(i) * self.params.attraction;
+    // groups, order parameters and spacing of the current state
+    pub fn get_stats(&self) -> FlockStats {
+        let grid = SpatialGrid::new(&self.boids, self.width, self.height, self.cell_size());
 
-        let noise = Vec2::new(
-            random_f32() - 0.5,
-            random_f32() - 0.5,
-        ).normalize() * self.params.noise;
+        let neighbors: Vec<Vec<usize>> = (0..self.boids.len())
+            .map(|i| self.neighbors(&grid, i).iter().map(|neighbor| neighbor.index).collect())
+            .collect();
+
+        let nearest: Vec<Option<f32>> = (0..self.boids.len())
+            .map(|i| grid.k_nearest(self.boids[i].pos, 1, |j| j != i).first().map(|neighbor| neighbor.dist))
+            .collect();
 
-        acc + noise
+        FlockStats::new(&self.boids, &neighbors, &nearest)
     }
 
-    fn separation_rule(&self, i: usize) -> Vec2 {
-        let boid = &self.boids[i];
-        let mut force = Vec2::ZERO;
-        let mut count = 0;
+    // BUFFER_STRIDE floats per boid, rewritten by step and by every call that adds, removes or moves boids
+    pub fn get_buffer(&self) -> &[f32] {
+        &self.buffer
+    }
 
-        for (j, other) in self.boids.iter().enumerate() {
-            if i == j {
-                continue;
+    // refreshes the buffer after boids changed outside of step
+    pub fn sync_buffer(&mut self) -> &[f32] {
+        write_buffer(&self.boids, &mut self.buffer);
+        &self.buffer
+    }
+
+    fn step_predators(&mut self, dt: f32) {
+        let params = self.species[0];
+
+        let bounds = Bounds {
+            width: self.width,
+            height: self.height,
+            region: self.containment.as_ref(),
+            rng: &self.rng,
+        };
+
+        for predator in self.predators.iter_mut() {
+            let nearest = self.boids
+                .iter()
+                .map(|boid| boid.pos)
+                .min_by(|a, b| (*a - predator.pos).magnitude().total_cmp(&(*b - predator.pos).magnitude()));
+
+            if let Some(target) = nearest {
+                predator.vel += (target - predator.pos).normalize() * params.predator_steer * dt;
             }
 
-            let diff = other.pos - boid.pos;
-            let dist = diff.magnitude();
+            predator.prev_pos = predator.pos;
+            predator.vel = predator.vel.limit(params.predator_speed);
+            predator.pos += predator.vel * dt;
+
+            bounds.constrain(params.boundary, &mut predator.pos, &mut predator.vel, &mut predator.prev_pos);
+        }
+    }
+
+    fn capture(&mut self) {
+        let radius = self.species[0].capture_radius;
+
+        if radius <= 0.0 {
+            return;
+        }
 
-            if dist > 0.0 && dist < self.params.perception_radius {
-                force -= diff.normalize() / dist;
-                count += 1;
+        let bounds = Bounds {
+            width: self.width,
+            height: self.height,
+            region: self.containment.as_ref(),
+            rng: &self.rng,
+        };
+
+        for (p, predator) in self.predators.iter().enumerate() {
+            for (b, boid) in self.boids.iter_mut().enumerate() {
+                if (boid.pos - predator.pos).magnitude() < radius {
+                    self.captures.push(Capture { predator: p, boid: b });
+
+                    boid.pos = bounds.random_point();
+                    boid.prev_pos = boid.pos;
+                    boid.trail.clear();
+                }
+            }
+        }
+
+        if self.captures.len() > MAX_CAPTURES {
+            self.captures.drain(..self.captures.len() - MAX_CAPTURES);
+        }
+    }
+
+    fn params(&self, i: usize) -> &Params {
+        &self.species[self.boids[i].species]
+    }
+
+    fn interaction(&self, i: usize, j: usize) -> Interaction {
+        self.get_interaction(self.boids[i].species, self.boids[j].species)
+    }
+
+    // largest radius any species looks at, so metric queries only touch adjacent cells
+    fn cell_size(&self) -> f32 {
+        self.species
+            .iter()
+            .map(|params| params.perception_radius.max(params.separation_radius))
+            .fold(1.0, f32::max)
+    }
+
+    fn neighbors(&self, grid: &SpatialGrid, i: usize) -> Vec<Neighbor> {
+        let params = self.params(i);
+        let pos = self.boids[i].pos;
+
+        match params.neighbor_mode {
+            NeighborMode::Metric => {
+                let radius = params.perception_radius.max(params.separation_radius);
+                let mut neighbors = Vec::new();
+
+                grid.within(pos, radius, |j, dist| {
+                    if self.accepts(i, j) {
+                        neighbors.push(Neighbor { index: j, dist });
+                    }
+                });
+
+                neighbors
             }
+
+            NeighborMode::Topological => grid.k_nearest(pos, params.k_neighbors, |j| self.accepts(i, j)),
         }
+    }
 
-        if count > 0 {
-            force.normalize()
-        } else {
-            Vec2::ZERO
+    // over the perception disc, or the disc reaching the farthest of the k neighbours
+    fn density(&self, i: usize, neighbors: &[Neighbor]) -> f32 {
+        let params = self.params(i);
+
+        let radius = match params.neighbor_mode {
+            NeighborMode::Metric => params.perception_radius.max(params.separation_radius),
+            NeighborMode::Topological => neighbors.iter().map(|n| n.dist).fold(0.0, f32::max),
+        };
+
+        if radius <= 0.0 {
+            return 0.0;
         }
+
+        neighbors.len() as f32 / (PI * radius * radius)
     }
 
-    fn alignment_rule(&self, i: usize) -> Vec2 {
+    // j is not ignored and outside of the blind spot behind i
+    fn accepts(&self, i: usize, j: usize) -> bool {
+        if i == j || self.interaction(i, j) == Interaction::Ignore {
+            return false;
+        }
+
         let boid = &self.boids[i];
-        let mut avg_vel = Vec2::ZERO;
-        let mut count = 0;
+        let view_angle = self.params(i).view_angle;
+        let heading = boid.vel.normalize();
+        let diff = self.boids[j].pos - boid.pos;
 
-        for (j, other) in self.boids.iter().enumerate() {
-            if i == j {
+        if view_angle >= TAU || heading.magnitude() <= 0.0 || diff.magnitude() <= 0.0 {
+            return true;
+        }
+
+        diff.normalize().dot(heading) >= (view_angle * 0.5).cos()
+    }
+
+    fn compute_acceleration(&self, i: usize, neighbors: &[Neighbor]) -> Vec2 {
+        let boid = &self.boids[i];
+        let params = self.params(i);
+
+        // following a leader replaces alignment and cohesion
+        let flocking = if boid.leader.is_some() { 0.0 } else { 1.0 };
+
+        let rules = [
+            (Separation.steer(boid, neighbors, self), params.separation),
+            (Alignment.steer(boid, neighbors, self), params.alignment * flocking),
+            (Cohesion.steer(boid, neighbors, self), params.cohesion * flocking),
+            (Attraction.steer(boid, neighbors, self), params.attraction),
+            (Avoidance.steer(boid, neighbors, self), params.avoidance),
+            (Flee.steer(boid, neighbors, self), params.flee),
+            (FollowLeader.steer(boid, neighbors, self), params.follow),
+            (FollowFlow.steer(boid, neighbors, self), params.flow),
+        ];
+
+        let custom = self.behaviors
+            .iter()
+            .map(|(behavior, weight)| (behavior.steer(boid, neighbors, self), *weight));
+
+        let noise = Vec2::new(
+            self.rng.next_f32() - 0.5,
+            self.rng.next_f32() - 0.5,
+        ).normalize() * params.noise;
+
+        let bounds = Bounds {
+            width: self.width,
+            height: self.height,
+            region: self.containment.as_ref(),
+            rng: &self.rng,
+        };
+
+        World::steering(params, boid.vel, rules.into_iter().chain(custom)) + noise + bounds.margin_force(params.boundary, boid.pos)
+    }
+
+    /*
+     * note:
+     * Reynolds steering, force = desired velocity - current velocity, clamped to max_force.
+     * the desired heading is the weighted sum of the rules, its speed the weighted average,
+     * so opposing rules turn the boid instead of braking it. rules with no opinion are left out
+     */
+    fn steering(params: &Params, vel: Vec2, rules: impl Iterator<Item = (Vec2, f32)>) -> Vec2 {
+        let mut heading = Vec2::ZERO;
+        let mut speed = 0.0;
+        let mut total = 0.0;
+
+        for (desired, weight) in rules {
+            let magnitude = desired.magnitude();
+
+            if magnitude == 0.0 || weight <= 0.0 {
                 continue;
             }
 
-            let diff = other.pos - boid.pos;
-            let dist = diff.magnitude();
+            heading += desired * weight;
+            speed += magnitude * weight;
+            total += weight;
+        }
+
+        if total == 0.0 {
+            return Vec2::ZERO;
+        }
+
+        let desired = heading.normalize() * (speed / total).min(1.0) * params.max_speed;
+
+        (desired - vel).limit(params.max_force)
+    }
 
-            if dist < self.params.perception_radius {
-                avg_vel += other.vel;
-                count += 1;
+    // turning rate, then max and min speed
+    fn limit_velocity(params: &Params, rng: &Rng, old: Vec2, new: Vec2, dt: f32) -> Vec2 {
+        let max_turn = params.max_turn_rate * dt;
+        let mut vel = new;
+
+        if old.magnitude() > 0.0 && new.magnitude() > 0.0 {
+            let angle = old.angle_to(new);
+
+            if angle.abs() > max_turn {
+                vel = old.normalize().rotate(max_turn.copysign(angle)) * new.magnitude();
             }
         }
 
-        if count > 0 {
-            let target = (avg_vel / count as f32).normalize();
-            let current = boid.vel.normalize();
-            (target - current).normalize()
-        } else {
-            Vec2::ZERO
+        let speed = vel.magnitude();
+
+        if speed < params.min_speed {
+            // a stalled boid keeps its last heading, or picks a random one
+            let dir = if speed > 0.0 { vel } else if old.magnitude() > 0.0 { old } else {
+                Vec2::new(rng.next_f32() - 0.5, rng.next_f32() - 0.5)
+            };
+
+            return dir.normalize() * params.min_speed;
         }
+
+        vel.limit(params.max_speed)
     }
+}
 
-    fn cohesion_rule(&self, i: usize) -> Vec2 {
-        let boid = &self.boids[i];
-        let mut center = Vec2::ZERO;
-        let mut count = 0;
+// what keeping agents inside needs, borrowed apart from the boids and predators
+struct Bounds<'a> {
+    width: f32,
+    height: f32,
+    region: Option<&'a Region>,
+    rng: &'a Rng,
+}
 
+impl Bounds<'_> {
+    // prev is moved along on wrap around and respawn, so interpolation doesn't cross the whole screen.
+    // true when the agent was put somewhere else (respawned or pushed back into the region)
+    fn constrain(&self, boundary: Boundary, pos: &mut Vec2, vel: &mut Vec2, prev: &mut Vec2) -> bool {
+        match boundary {
+            Boundary::Bounce | Boundary::SoftMargin { .. } => {
+                if pos.x < 0.0 {
+                    pos.x = 0.0;
+                    vel.x *= -1.0;
+                } else if pos.x > self.width {
+                    pos.x = self.width;
+                    vel.x *= -1.0;
+                }
 
-        for (j, other) in self.boids.iter().enumerate() {
-            if i == j {
-                continue;
+                if pos.y < 0.0 {
+                    pos.y = 0.0;
+                    vel.y *= -1.0;
+                } else if pos.y > self.height {
+                    pos.y = self.height;
+                    vel.y *= -1.0;
+                }
+            }
+
+            Boundary::Wrap => {
+                let before = *pos;
+
+                if pos.x < 0.0 {
+                    pos.x += self.width;
+                }
+
+                if pos.y < 0.0 {
+                    pos.y += self.height;
+                }
+
+                if pos.x > self.width {
+                    pos.x -= self.width;
+                }
+
+                if pos.y > self.height {
+                    pos.y -= self.height;
+                }
+
+                *prev += *pos - before;
             }
 
-            let diff = other.pos - boid.pos;
-            let dist = diff.magnitude();
+            Boundary::Open => {
+                let outside = pos.x < 0.0 || pos.y < 0.0 || pos.x > self.width || pos.y > self.height
+                    || self.region.is_some_and(|region| !region.contains(*pos));
+
+                if outside {
+                    self.respawn(pos, vel);
+                    *prev = *pos;
+                }
 
-            if dist < self.params.perception_radius {
-                center += other.pos;
-                count += 1;
+                return outside;
             }
         }
 
-        if count > 0 {
-            ((center / count as f32) - boid.pos).normalize() 
-        } else {
-            Vec2::ZERO
+        // back onto the region boundary, reflecting the velocity
+        if let Some(region) = self.region
+            && !region.contains(*pos)
+        {
+            let (_, closest, normal) = region.boundary(*pos);
+            let before = *pos;
+
+            *pos = closest + normal * 1e-2;
+            *prev += *pos - before;
+
+            if vel.dot(normal) < 0.0 {
+                *vel -= normal * (2.0 * vel.dot(normal));
+            }
+
+            return true;
         }
+
+        false
     }
 
-    fn attraction_rule(&self, i: usize) -> Vec2 {
-        if let Some(target) = self.attractor {
-            let dir = target - self.boids[i].pos;
-            if dir.magnitude() > 0.0 {
-                dir.normalize()
-            } else {
-                Vec2::ZERO
+    // inwards, growing linearly from 0.0 at margin px to force at the edge
+    fn margin_force(&self, boundary: Boundary, pos: Vec2) -> Vec2 {
+        let Boundary::SoftMargin { margin, force } = boundary else {
+            return Vec2::ZERO;
+        };
+
+        if margin <= 0.0 {
+            return Vec2::ZERO;
+        }
+
+        let push = |distance: f32| force * (1.0 - distance / margin).clamp(0.0, 1.0);
+
+        let mut total = Vec2::new(
+            push(pos.x) - push(self.width - pos.x),
+            push(pos.y) - push(self.height - pos.y),
+        );
+
+        if let Some(region) = self.region {
+            let (distance, _, normal) = region.boundary(pos);
+            let distance = if region.contains(pos) { distance } else { 0.0 };
+
+            total += normal * push(distance);
+        }
+
+        total
+    }
+
+    // on a random world edge heading inwards, or anywhere inside the containment region heading anywhere
+    fn respawn(&self, pos: &mut Vec2, vel: &mut Vec2) {
+        let speed = vel.magnitude();
+
+        if self.region.is_some() {
+            *pos = self.random_point();
+            *vel = Vec2::new(1.0, 0.0).rotate(self.rng.next_f32() * TAU) * speed;
+            return;
+        }
+
+        let t = self.rng.next_f32();
+
+        let (edge, inward) = match (self.rng.next_f32() * 4.0) as usize {
+            0 => (Vec2::new(t * self.width, 0.0), Vec2::new(0.0, 1.0)),
+            1 => (Vec2::new(t * self.width, self.height), Vec2::new(0.0, -1.0)),
+            2 => (Vec2::new(0.0, t * self.height), Vec2::new(1.0, 0.0)),
+            _ => (Vec2::new(self.width, t * self.height), Vec2::new(-1.0, 0.0)),
+        };
+
+        // up to 45 degrees off the inward normal
+        let angle = (self.rng.next_f32() - 0.5) * std::f32::consts::FRAC_PI_2;
+
+        *pos = edge;
+        *vel = inward.rotate(angle) * speed;
+    }
+
+    // uniform inside the world, or inside the part of the containment region within the world by rejection sampling
+    fn random_point(&self) -> Vec2 {
+        let random_in = |min: Vec2, max: Vec2| {
+            Vec2::new(
+                min.x + self.rng.next_f32() * (max.x - min.x),
+                min.y + self.rng.next_f32() * (max.y - min.y),
+            )
+        };
+
+        let Some(region) = self.region else {
+            return random_in(Vec2::ZERO, Vec2::new(self.width, self.height));
+        };
+
+        let (min, max) = region.bounds();
+        let min = Vec2::new(min.x.max(0.0), min.y.max(0.0));
+        let max = Vec2::new(max.x.min(self.width), max.y.min(self.height));
+
+        if min.x <= max.x && min.y <= max.y {
+            for _ in 0..32 {
+                let point = random_in(min, max);
+
+                if region.contains(point) {
+                    return point;
+                }
             }
-        } else {
-            Vec2::ZERO
         }
+
+        // thin region or barely inside the world, settle for its boundary
+        let (_, closest, normal) = region.boundary(random_in(min, max));
+        let point = closest + normal * 1e-2;
+
+        Vec2::new(point.x.clamp(0.0, self.width), point.y.clamp(0.0, self.height))
     }
-}
\ No newline at end of file
+}
+
+/*
+ * note:
+ * a snapshot holds everything but the custom behaviors (trait objects),
+ * restore keeps the ones already registered on the world
+ */
+#[cfg(feature = "serde")]
+impl World {
+    pub fn to_json(&self) -> Result<String, SnapshotError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<World, SnapshotError> {
+        let mut world: World = serde_json::from_str(json)?;
+        world.validate()?;
+        world.sync_buffer();
+        Ok(world)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnapshotError> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<World, SnapshotError> {
+        let mut world: World = bincode::deserialize(bytes)?;
+        world.validate()?;
+        world.sync_buffer();
+        Ok(world)
+    }
+
+    // what the setters assert, checked again since a snapshot bypasses them
+    pub fn validate(&self) -> Result<(), SnapshotError> {
+        let ensure = |valid: bool, reason: &str| if valid { Ok(()) } else { Err(SnapshotError::Invalid(reason.to_string())) };
+        let count = self.species.len();
+
+        ensure(count > 0, "at least one species is required")?;
+        ensure(self.interactions.len() == count * count, "interactions must have species_count^2 entries")?;
+
+        for (species, params) in self.species.iter().enumerate() {
+            params.validate().map_err(|e| SnapshotError::Invalid(format!("species {}: {}", species, e)))?;
+
+            if let Boundary::SoftMargin { margin, force } = params.boundary {
+                ensure(margin >= 0.0 && force >= 0.0, "soft margin and force must not be negative")?;
+            }
+        }
+
+        ensure(self.width > 0.0 && self.height > 0.0, "width and height must be positive")?;
+        ensure(self.fixed_dt > 0.0, "fixed dt must be positive")?;
+
+        ensure(self.containment.as_ref().is_none_or(Region::is_valid), "region must have a positive radius or at least 3 points")?;
+        ensure(self.flow_field.as_ref().is_none_or(FlowField::is_valid), "flow field must be a non-empty grid of cols * rows vectors or have a positive scale")?;
+
+        ensure(self.leaders.iter().all(|leader| leader.path.is_valid()), "leader paths must have at least 2 points and matching samples")?;
+
+        for boid in &self.boids {
+            ensure(boid.species < count, "boid species must be between 0 and species_count - 1")?;
+            ensure(boid.leader.is_none_or(|(leader, _)| leader < self.leaders.len()), "boid leader must be between 0 and leaders - 1")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn restore(&mut self, snapshot: World) {
+        let behaviors = std::mem::take(&mut self.behaviors);
+
+        *self = snapshot;
+        self.behaviors = behaviors;
+        self.sync_buffer();
+    }
+}
@@ -0,0 +1,345 @@
+use crate::{ParamError, Params};
+use crate::random::Rng;
+use crate::{Vec2, Vec3};
+
+use std::f32::consts::TAU;
+
+/*
+ * note:
+ * 3D sibling of World with the flocking rules (separation, alignment, cohesion),
+ * a single attractor and noise, steered the same way as World.
+ * obstacles, predators, species and fields stay 2D only
+ */
+
+pub struct Boid3D {
+    pub pos: Vec3,
+    pub vel: Vec3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bounds3D {
+    Box, // bounce off the walls
+    Toroidal, // wrap around on every axis
+}
+
+pub struct World3D {
+    boids: Vec<Boid3D>,
+    params: Params,
+    size: Vec3, // width, height, depth
+    bounds: Bounds3D,
+    attractor: Option<Vec3>,
+    rng: Rng,
+}
+
+// perspective camera looking down +z from eye
+#[derive(Clone, Copy, Debug)]
+pub struct Projection {
+    pub eye: Vec3,
+    pub screen_center: Vec2,
+    pub focal_length: f32,
+}
+
+impl World3D {
+    pub fn new(n: usize, width: f32, height: f32, depth: f32) -> Self {
+        assert!(width > 0.0 && height > 0.0 && depth > 0.0, "Error: width, height and depth must be positive");
+
+        let rng = Rng::from_entropy();
+        let mut boids = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            boids.push(Boid3D {
+                pos: Vec3::new(
+                    rng.next_f32() * width,
+                    rng.next_f32() * height,
+                    rng.next_f32() * depth,
+                ),
+                vel: Vec3::new(
+                    rng.next_f32() - 0.5,
+                    rng.next_f32() - 0.5,
+                    rng.next_f32() - 0.5,
+                ),
+            });
+        }
+
+        Self {
+            boids,
+            params: Params::default(),
+            size: Vec3::new(width, height, depth),
+            bounds: Bounds3D::Box,
+            attractor: None,
+            rng,
+        }
+    }
+
+    // same names and ranges as World::set_params, 2D only params are accepted but unused
+    pub fn set_params(&mut self, name: &str, value: f32) -> Result<(), ParamError> {
+        self.params.set(name, value)
+    }
+
+    pub fn get_param(&self, name: &str) -> Result<f32, ParamError> {
+        self.params.get(name)
+    }
+
+    pub fn set_bounds(&mut self, bounds: Bounds3D) {
+        self.bounds = bounds;
+    }
+
+    pub fn get_boids(&self) -> &[Boid3D] {
+        &self.boids
+    }
+
+    pub fn get_size(&self) -> Vec3 {
+        self.size
+    }
+
+    pub fn set_attractor(&mut self, pos: Option<Vec3>) {
+        self.attractor = pos;
+    }
+
+    // eye in front of the z = 0 face, which projects at scale 1.0 onto the world xy
+    pub fn projection(&self, focal_length: f32) -> Projection {
+        let center = Vec2::new(self.size.x * 0.5, self.size.y * 0.5);
+
+        Projection {
+            eye: Vec3::new(center.x, center.y, -focal_length),
+            screen_center: center,
+            focal_length,
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        let grid = Grid3D::new(&self.boids, self.size, self.params.perception_radius.max(self.params.separation_radius));
+
+        let forces: Vec<Vec3> = (0..self.boids.len())
+            .map(|i| self.compute_force(i, &grid))
+            .collect();
+
+        let params = &self.params;
+
+        for (boid, force) in self.boids.iter_mut().zip(forces) {
+            let old = boid.vel;
+            let new = old + force / params.mass * dt;
+
+            boid.vel = World3D::limit_velocity(params, old, new, dt);
+            boid.pos += boid.vel * dt;
+
+            World3D::constrain(self.bounds, self.size, boid);
+        }
+    }
+
+    fn compute_force(&self, i: usize, grid: &Grid3D) -> Vec3 {
+        let params = &self.params;
+        let boid = &self.boids[i];
+        let heading = boid.vel.normalize();
+        let min_dot = (params.view_angle * 0.5).cos();
+
+        let mut separation = Vec3::ZERO;
+        let mut avg_vel = Vec3::ZERO;
+        let mut center = Vec3::ZERO;
+        let mut total = 0.0;
+
+        grid.within(&self.boids, boid.pos, params.perception_radius.max(params.separation_radius), |j, dist| {
+            let diff = self.boids[j].pos - boid.pos;
+
+            if j == i || dist <= 0.0 {
+                return;
+            }
+
+            // blind spot behind the boid
+            if params.view_angle < TAU && heading.magnitude() > 0.0 && diff.normalize().dot(heading) < min_dot {
+                return;
+            }
+
+            if dist < params.separation_radius {
+                separation -= diff.normalize() / dist;
+            }
+
+            if dist < params.perception_radius {
+                let weight = if params.distance_weighting { 1.0 - dist / params.perception_radius } else { 1.0 };
+
+                avg_vel += self.boids[j].vel * weight;
+                center += self.boids[j].pos * weight;
+                total += weight;
+            }
+        });
+
+        let (alignment, cohesion) = if total > 0.0 {
+            ((avg_vel / total).normalize(), (center / total - boid.pos).normalize())
+        } else {
+            (Vec3::ZERO, Vec3::ZERO)
+        };
+
+        let attraction = match self.attractor {
+            Some(target) => (target - boid.pos).normalize(),
+            None => Vec3::ZERO,
+        };
+
+        let rules = [
+            (separation.normalize(), params.separation),
+            (alignment, params.alignment),
+            (cohesion, params.cohesion),
+            (attraction, params.attraction),
+        ];
+
+        let noise = Vec3::new(
+            self.rng.next_f32() - 0.5,
+            self.rng.next_f32() - 0.5,
+            self.rng.next_f32() - 0.5,
+        ).normalize() * params.noise;
+
+        World3D::steering(params, boid.vel, &rules) + noise
+    }
+
+    // same blend as World::steering
+    fn steering(params: &Params, vel: Vec3, rules: &[(Vec3, f32)]) -> Vec3 {
+        let mut heading = Vec3::ZERO;
+        let mut speed = 0.0;
+        let mut total = 0.0;
+
+        for &(desired, weight) in rules {
+            let magnitude = desired.magnitude();
+
+            if magnitude == 0.0 || weight <= 0.0 {
+                continue;
+            }
+
+            heading += desired * weight;
+            speed += magnitude * weight;
+            total += weight;
+        }
+
+        if total == 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let desired = heading.normalize() * (speed / total).min(1.0) * params.max_speed;
+
+        (desired - vel).limit(params.max_force)
+    }
+
+    // turning rate (slerp towards the new heading), then max and min speed
+    fn limit_velocity(params: &Params, old: Vec3, new: Vec3, dt: f32) -> Vec3 {
+        let max_turn = params.max_turn_rate * dt;
+        let mut vel = new;
+
+        if old.magnitude() > 0.0 && new.magnitude() > 0.0 {
+            let a = old.normalize();
+            let b = new.normalize();
+            let angle = a.dot(b).clamp(-1.0, 1.0).acos();
+
+            if angle > max_turn && angle.sin() > 1e-4 {
+                let t = max_turn / angle;
+                let dir = (a * ((1.0 - t) * angle).sin() + b * (t * angle).sin()) / angle.sin();
+
+                vel = dir * new.magnitude();
+            }
+        }
+
+        let speed = vel.magnitude();
+
+        if speed < params.min_speed {
+            let dir = if speed > 0.0 { vel } else { old };
+            return dir.normalize() * params.min_speed;
+        }
+
+        vel.limit(params.max_speed)
+    }
+
+    fn constrain(bounds: Bounds3D, size: Vec3, boid: &mut Boid3D) {
+        let axes = [
+            (&mut boid.pos.x, &mut boid.vel.x, size.x),
+            (&mut boid.pos.y, &mut boid.vel.y, size.y),
+            (&mut boid.pos.z, &mut boid.vel.z, size.z),
+        ];
+
+        for (pos, vel, max) in axes {
+            match bounds {
+                Bounds3D::Box => {
+                    if *pos < 0.0 {
+                        *pos = 0.0;
+                        *vel *= -1.0;
+                    } else if *pos > max {
+                        *pos = max;
+                        *vel *= -1.0;
+                    }
+                }
+
+                Bounds3D::Toroidal => *pos = pos.rem_euclid(max),
+            }
+        }
+    }
+}
+
+impl Projection {
+    // (screen position, scale for sizes), None behind the eye
+    pub fn project(&self, pos: Vec3) -> Option<(Vec2, f32)> {
+        let depth = pos.z - self.eye.z;
+
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let scale = self.focal_length / depth;
+        let screen = Vec2::new(
+            self.screen_center.x + (pos.x - self.eye.x) * scale,
+            self.screen_center.y + (pos.y - self.eye.y) * scale,
+        );
+
+        Some((screen, scale))
+    }
+}
+
+// uniform grid rebuilt every step, like SpatialGrid
+struct Grid3D {
+    cell_size: f32,
+    dims: [usize; 3],
+    cells: Vec<Vec<usize>>,
+}
+
+impl Grid3D {
+    fn new(boids: &[Boid3D], size: Vec3, cell_size: f32) -> Self {
+        let cell_size = cell_size.max(1.0);
+        let dim = |length: f32| ((length / cell_size).ceil() as usize).max(1);
+        let dims = [dim(size.x), dim(size.y), dim(size.z)];
+
+        let mut grid = Self {
+            cell_size,
+            dims,
+            cells: vec![Vec::new(); dims[0] * dims[1] * dims[2]],
+        };
+
+        for (i, boid) in boids.iter().enumerate() {
+            let [x, y, z] = grid.cell_of(boid.pos);
+            grid.cells[x + (y + z * dims[1]) * dims[0]].push(i);
+        }
+
+        grid
+    }
+
+    // calls f(index, dist) for every boid closer than radius
+    fn within(&self, boids: &[Boid3D], pos: Vec3, radius: f32, mut f: impl FnMut(usize, f32)) {
+        let center = self.cell_of(pos);
+        let reach = (radius / self.cell_size).ceil() as usize;
+        let range = |axis: usize| center[axis].saturating_sub(reach)..=(center[axis] + reach).min(self.dims[axis] - 1);
+
+        for z in range(2) {
+            for y in range(1) {
+                for x in range(0) {
+                    for &i in &self.cells[x + (y + z * self.dims[1]) * self.dims[0]] {
+                        let dist = (boids[i].pos - pos).magnitude();
+
+                        if dist < radius {
+                            f(i, dist);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn cell_of(&self, pos: Vec3) -> [usize; 3] {
+        let cell = |value: f32, axis: usize| ((value / self.cell_size).floor().max(0.0) as usize).min(self.dims[axis] - 1);
+
+        [cell(pos.x, 0), cell(pos.y, 1), cell(pos.z, 2)]
+    }
+}
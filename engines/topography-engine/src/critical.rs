@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use crate::Point;
+
+/*
+ * references:
+ *
+ * topographic prominence:
+ * https://en.wikipedia.org/wiki/Topographic_prominence
+ *
+ * merge trees / persistence:
+ * https://en.wikipedia.org/wiki/Merge_tree
+ * https://en.wikipedia.org/wiki/Morse%E2%80%93Smale_complex
+ */
+
+/*
+ * note:
+ * peaks and join saddles come from a descending sweep over 8-connected cells,
+ * pits and split saddles from an ascending sweep over 4-connected cells,
+ * so a saddle shows up in only one of them
+ */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CriticalKind {
+    Peak,
+    Pit,
+    Saddle,
+}
+
+#[derive(Clone, Copy)]
+pub struct CriticalPoint {
+    pub kind: CriticalKind,
+    pub point: Point,
+    pub height: f32,
+    pub prominence: f32, // depth for pits, persistence of the pair it closes for saddles
+}
+
+// saddle separating two peaks (or two pits), indices into CriticalPoints::points
+#[derive(Clone, Copy)]
+pub struct SaddleLink {
+    pub saddle: usize,
+    pub extrema: [usize; 2], // [higher (deeper) survivor, extremum merged at this saddle]
+}
+
+pub struct CriticalPoints {
+    pub points: Vec<CriticalPoint>,
+    pub links: Vec<SaddleLink>,
+}
+
+struct Merge {
+    saddle: usize,
+    survivor: usize,
+    dying: usize,
+    persistence: f32,
+}
+
+struct Sweep {
+    extrema: Vec<(usize, f32)>, // (cell, prominence)
+    merges: Vec<Merge>,
+}
+
+impl CriticalPoints {
+    pub fn new(map: &[f32], size: usize, min_prominence: f32) -> Self {
+        let peaks = CriticalPoints::sweep(map, size, 1.0, true);
+        let pits = CriticalPoints::sweep(map, size, -1.0, false);
+
+        let mut graph = CriticalPoints {
+            points: Vec::new(),
+            links: Vec::new(),
+        };
+
+        let mut extremum_index = HashMap::new();
+        let mut saddle_index = HashMap::new();
+
+        for (sweep, kind) in [(&peaks, CriticalKind::Peak), (&pits, CriticalKind::Pit)] {
+            for &(cell, prominence) in &sweep.extrema {
+                if prominence < min_prominence {
+                    continue;
+                }
+
+                extremum_index.insert((kind, cell), graph.points.len());
+                graph.push(map, size, kind, cell, prominence);
+            }
+        }
+
+        for (sweep, kind) in [(&peaks, CriticalKind::Peak), (&pits, CriticalKind::Pit)] {
+            for merge in &sweep.merges {
+                if merge.persistence < min_prominence {
+                    continue;
+                }
+
+                // the survivor always outlives the dying extremum, so both passed the filter
+                let survivor = extremum_index[&(kind, merge.survivor)];
+                let dying = extremum_index[&(kind, merge.dying)];
+
+                let saddle = *saddle_index.entry(merge.saddle).or_insert_with(|| {
+                    graph.push(map, size, CriticalKind::Saddle, merge.saddle, merge.persistence);
+                    graph.points.len() - 1
+                });
+
+                let point = &mut graph.points[saddle];
+                point.prominence = point.prominence.max(merge.persistence);
+
+                graph.links.push(SaddleLink {
+                    saddle,
+                    extrema: [survivor, dying],
+                });
+            }
+        }
+
+        graph
+    }
+
+    pub fn of_kind(&self, kind: CriticalKind) -> impl Iterator<Item = &CriticalPoint> {
+        self.points.iter().filter(move |point| point.kind == kind)
+    }
+
+    fn push(&mut self, map: &[f32], size: usize, kind: CriticalKind, cell: usize, prominence: f32) {
+        self.points.push(CriticalPoint {
+            kind,
+            point: Point::new((cell % size) as f32, (cell / size) as f32),
+            height: map[cell],
+            prominence,
+        });
+    }
+
+    // union-find over superlevel sets of sign * map, the lower extremum dies at every merge
+    fn sweep(map: &[f32], size: usize, sign: f32, diagonals: bool) -> Sweep {
+        let cells = size * size;
+
+        let mut order: Vec<usize> = (0..cells).collect();
+        order.sort_by(|a, b| (map[*b] * sign).total_cmp(&(map[*a] * sign)).then(a.cmp(b)));
+
+        // rank breaks ties between equal heights, lower rank = higher
+        let mut rank = vec![0; cells];
+        for (position, cell) in order.iter().enumerate() {
+            rank[*cell] = position;
+        }
+
+        // roots are always the extremum of their component
+        let mut parent: Vec<usize> = (0..cells).collect();
+        let mut processed = vec![false; cells];
+
+        let mut born = Vec::new();
+        let mut prominence = vec![0.0; cells];
+        let mut merges = Vec::new();
+
+        let find = |parent: &mut Vec<usize>, mut cell: usize| {
+            while parent[cell] != cell {
+                parent[cell] = parent[parent[cell]];
+                cell = parent[cell];
+            }
+            cell
+        };
+
+        let offsets: &[(isize, isize)] = if diagonals {
+            &[(1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1)]
+        } else {
+            &[(1, 0), (0, -1), (-1, 0), (0, 1)]
+        };
+
+        for &cell in &order {
+            let x = (cell % size) as isize;
+            let y = (cell / size) as isize;
+
+            let mut roots: Vec<usize> = Vec::with_capacity(offsets.len());
+
+            for (dx, dy) in offsets {
+                let nx = x + dx;
+                let ny = y + dy;
+
+                if nx < 0 || ny < 0 || nx >= size as isize || ny >= size as isize {
+                    continue;
+                }
+
+                let next = nx as usize + ny as usize * size;
+
+                if processed[next] {
+                    let root = find(&mut parent, next);
+                    if !roots.contains(&root) {
+                        roots.push(root);
+                    }
+                }
+            }
+
+            processed[cell] = true;
+
+            if roots.is_empty() {
+                born.push(cell);
+                continue;
+            }
+
+            roots.sort_by_key(|root| rank[*root]);
+            let survivor = roots[0];
+
+            for &dying in &roots[1..] {
+                let persistence = (map[dying] - map[cell]) * sign;
+
+                prominence[dying] = persistence;
+                merges.push(Merge {
+                    saddle: cell,
+                    survivor,
+                    dying,
+                    persistence,
+                });
+
+                parent[dying] = survivor;
+            }
+
+            parent[cell] = survivor;
+        }
+
+        // the global extremum never dies, measured against the opposite end of the map
+        if let (Some(first), Some(last)) = (order.first(), order.last()) {
+            prominence[*first] = (map[*first] - map[*last]) * sign;
+        }
+
+        Sweep {
+            extrema: born.into_iter().map(|cell| (cell, prominence[cell])).collect(),
+            merges,
+        }
+    }
+}
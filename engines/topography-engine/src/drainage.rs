@@ -0,0 +1,300 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+use crate::Point;
+
+/*
+ * references:
+ *
+ * priority-flood depression filling:
+ * https://arxiv.org/abs/1511.04463
+ *
+ * D8 / D-infinity flow direction:
+ * https://www.whiteboxgeo.com/manual/wbt_book/available_tools/hydrological_analysis.html
+ * https://doi.org/10.1029/96WR03137
+ */
+
+// counterclockwise, starting from east (y grows downwards)
+const OFFSETS: [(isize, isize); 8] = [(1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1)];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    D8, // all flow to the steepest neighbour
+    DInfinity, // flow split between the two neighbours of the steepest facet
+}
+
+// polyline with the accumulated flow (upstream cells) at each point
+pub struct River {
+    pub points: Vec<Point>,
+    pub flow: Vec<f32>,
+}
+
+pub struct Drainage {
+    size: usize,
+    filled: Vec<f32>, // depression free height map
+    receivers: Vec<[(usize, f32); 2]>, // (cell, proportion) pairs, proportion 0.0 = unused
+    accumulation: Vec<f32>,
+}
+
+// min-heap entry for the priority flood
+struct Cell {
+    height: f32,
+    index: usize,
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.height.total_cmp(&self.height).then(other.index.cmp(&self.index))
+    }
+}
+
+impl Drainage {
+    pub fn new(map: &[f32], size: usize, direction: FlowDirection) -> Self {
+        let filled = Drainage::fill_depressions(map, size);
+
+        let receivers = match direction {
+            FlowDirection::D8 => Drainage::d8(&filled, size),
+            FlowDirection::DInfinity => Drainage::d_infinity(&filled, size),
+        };
+
+        let accumulation = Drainage::accumulate(&filled, &receivers);
+
+        Self {
+            size,
+            filled,
+            receivers,
+            accumulation,
+        }
+    }
+
+    pub fn get_filled(&self) -> &Vec<f32> {
+        &self.filled
+    }
+
+    pub fn get_accumulation(&self) -> &Vec<f32> {
+        &self.accumulation
+    }
+
+    // traces every cell with accumulation >= threshold downstream, joining at confluences
+    pub fn rivers(&self, threshold: f32) -> Vec<River> {
+        let size = self.size;
+        let cells = size * size;
+
+        let is_river: Vec<bool> = self.accumulation.iter().map(|acc| *acc >= threshold).collect();
+        let main: Vec<Option<usize>> = (0..cells).map(|i| self.main_receiver(i)).collect();
+
+        let mut has_upstream = vec![false; cells];
+        for i in 0..cells {
+            if let Some(r) = main[i] && is_river[i] && is_river[r] {
+                has_upstream[r] = true;
+            }
+        }
+
+        let mut sources: Vec<usize> = (0..cells).filter(|i| is_river[*i] && !has_upstream[*i]).collect();
+        sources.sort_by(|a, b| self.filled[*b].total_cmp(&self.filled[*a]).then(a.cmp(b)));
+
+        let mut visited = vec![false; cells];
+        let mut rivers = Vec::new();
+
+        for source in sources {
+            let mut river = River { points: Vec::new(), flow: Vec::new() };
+            let mut cell = source;
+
+            loop {
+                river.points.push(Point::new((cell % size) as f32, (cell / size) as f32));
+                river.flow.push(self.accumulation[cell]);
+
+                // confluence, the junction point is kept to connect both polylines
+                if visited[cell] {
+                    break;
+                }
+
+                visited[cell] = true;
+
+                match main[cell] {
+                    Some(next) if is_river[next] => cell = next,
+                    _ => break,
+                }
+            }
+
+            if river.points.len() > 1 {
+                rivers.push(river);
+            }
+        }
+
+        rivers
+    }
+
+    fn main_receiver(&self, index: usize) -> Option<usize> {
+        let [first, second] = self.receivers[index];
+
+        if first.1 <= 0.0 && second.1 <= 0.0 {
+            None
+        } else if first.1 >= second.1 {
+            Some(first.0)
+        } else {
+            Some(second.0)
+        }
+    }
+
+    fn neighbour(size: usize, index: usize, direction: usize) -> Option<usize> {
+        let (dx, dy) = OFFSETS[direction % 8];
+        let x = (index % size) as isize + dx;
+        let y = (index / size) as isize + dy;
+
+        if x < 0 || y < 0 || x >= size as isize || y >= size as isize {
+            None
+        } else {
+            Some(x as usize + y as usize * size)
+        }
+    }
+
+    fn is_border(size: usize, index: usize) -> bool {
+        let x = index % size;
+        let y = index / size;
+
+        x == 0 || y == 0 || x == size - 1 || y == size - 1
+    }
+
+    // priority-flood + epsilon, every cell ends up with a strictly descending path to the border
+    fn fill_depressions(map: &[f32], size: usize) -> Vec<f32> {
+        let mut filled = map.to_vec();
+        let mut closed = vec![false; size * size];
+        let mut heap = BinaryHeap::new();
+
+        for index in 0..size * size {
+            if Drainage::is_border(size, index) {
+                closed[index] = true;
+                heap.push(Cell { height: filled[index], index });
+            }
+        }
+
+        while let Some(Cell { height, index }) = heap.pop() {
+            for direction in 0..8 {
+                let Some(next) = Drainage::neighbour(size, index, direction) else {
+                    continue;
+                };
+
+                if closed[next] {
+                    continue;
+                }
+
+                closed[next] = true;
+                filled[next] = filled[next].max(height.next_up());
+                heap.push(Cell { height: filled[next], index: next });
+            }
+        }
+
+        filled
+    }
+
+    fn d8(filled: &[f32], size: usize) -> Vec<[(usize, f32); 2]> {
+        let mut receivers = vec![[(0, 0.0); 2]; size * size];
+
+        for (index, receiver) in receivers.iter_mut().enumerate() {
+            // border cells drain off the map
+            if Drainage::is_border(size, index) {
+                continue;
+            }
+
+            let mut steepest = 0.0;
+
+            for direction in 0..8 {
+                let Some(next) = Drainage::neighbour(size, index, direction) else {
+                    continue;
+                };
+
+                let distance = if direction % 2 == 0 { 1.0 } else { SQRT_2 };
+                let slope = (filled[index] - filled[next]) / distance;
+
+                if slope > steepest {
+                    steepest = slope;
+                    receiver[0] = (next, 1.0);
+                }
+            }
+        }
+
+        receivers
+    }
+
+    fn d_infinity(filled: &[f32], size: usize) -> Vec<[(usize, f32); 2]> {
+        let mut receivers = vec![[(0, 0.0); 2]; size * size];
+
+        for (index, receiver) in receivers.iter_mut().enumerate() {
+            if Drainage::is_border(size, index) {
+                continue;
+            }
+
+            let e0 = filled[index];
+            let mut steepest = 0.0;
+
+            // 8 triangular facets, each made of a cardinal and an adjacent diagonal neighbour
+            for cardinal in (0..8).step_by(2) {
+                for diagonal in [cardinal + 1, cardinal + 7] {
+                    let (Some(n1), Some(n2)) = (
+                        Drainage::neighbour(size, index, cardinal),
+                        Drainage::neighbour(size, index, diagonal),
+                    ) else {
+                        continue;
+                    };
+
+                    let s1 = e0 - filled[n1];
+                    let s2 = filled[n1] - filled[n2];
+
+                    let mut angle = s2.atan2(s1);
+                    let mut slope = (s1 * s1 + s2 * s2).sqrt();
+
+                    if angle < 0.0 {
+                        angle = 0.0;
+                        slope = s1;
+                    } else if angle > FRAC_PI_4 {
+                        angle = FRAC_PI_4;
+                        slope = (e0 - filled[n2]) / SQRT_2;
+                    }
+
+                    if slope > steepest {
+                        steepest = slope;
+
+                        let proportion = angle / FRAC_PI_4;
+                        *receiver = [(n1, 1.0 - proportion), (n2, proportion)];
+                    }
+                }
+            }
+        }
+
+        receivers
+    }
+
+    fn accumulate(filled: &[f32], receivers: &[[(usize, f32); 2]]) -> Vec<f32> {
+        let mut accumulation = vec![1.0; filled.len()];
+
+        // receivers are always lower, so highest to lowest visits donors first
+        let mut order: Vec<usize> = (0..filled.len()).collect();
+        order.sort_by(|a, b| filled[*b].total_cmp(&filled[*a]).then(a.cmp(b)));
+
+        for index in order {
+            for (receiver, proportion) in receivers[index] {
+                if proportion > 0.0 {
+                    accumulation[receiver] += accumulation[index] * proportion;
+                }
+            }
+        }
+
+        accumulation
+    }
+}
@@ -1,7 +1,15 @@
 mod random;
 mod topography;
 mod math;
+mod mask;
+mod postprocess;
+mod drainage;
+mod critical;
 
 pub use random::random_f32;
 pub use topography::Topography;
-pub use math::{Point, Segment};
\ No newline at end of file
+pub use math::{Point, Segment};
+pub use mask::Mask;
+pub use postprocess::PostProcess;
+pub use drainage::{Drainage, FlowDirection, River};
+pub use critical::{CriticalKind, CriticalPoint, CriticalPoints, SaddleLink};
\ No newline at end of file
@@ -0,0 +1,73 @@
+use crate::Point;
+use crate::math::smoothstep;
+
+/*
+ * note:
+ * masks are sampled in normalized map coordinates [0, 1]
+ * 1.0 keeps the terrain as is, 0.0 flattens it to sea level
+ */
+#[derive(Clone)]
+pub enum Mask {
+    // sea level at radius, fading in over falloff towards the center
+    Radial { center: Point, radius: f32, falloff: f32 },
+    // sea level at the map edges inset by margin, fading in over falloff
+    RoundedRect { margin: f32, corner_radius: f32, falloff: f32 },
+    // user supplied grid, bilinearly sampled over the whole map
+    Grid { width: usize, height: usize, values: Vec<f32> },
+}
+
+impl Mask {
+    pub fn radial(radius: f32, falloff: f32) -> Self {
+        Mask::Radial { center: Point::new(0.5, 0.5), radius, falloff }
+    }
+
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        match self {
+            Mask::Radial { center, radius, falloff } => {
+                let dx = u - center.x;
+                let dy = v - center.y;
+                let dist = (dx * dx + dy * dy).sqrt();
+
+                1.0 - smoothstep(radius - falloff, *radius, dist)
+            }
+
+            Mask::RoundedRect { margin, corner_radius, falloff } => {
+                // signed distance to the rounded rectangle, negative inside
+                let half = 0.5 - margin;
+                let qx = (u - 0.5).abs() - half + corner_radius;
+                let qy = (v - 0.5).abs() - half + corner_radius;
+
+                let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+                let inside = qx.max(qy).min(0.0);
+                let dist = outside + inside - corner_radius;
+
+                1.0 - smoothstep(-falloff, 0.0, dist)
+            }
+
+            Mask::Grid { width, height, values } => {
+                let gx = u.clamp(0.0, 1.0) * (*width - 1) as f32;
+                let gy = v.clamp(0.0, 1.0) * (*height - 1) as f32;
+
+                let x0 = (gx as usize).min(width - 1);
+                let y0 = (gy as usize).min(height - 1);
+                let x1 = (x0 + 1).min(width - 1);
+                let y1 = (y0 + 1).min(height - 1);
+
+                let tx = gx - x0 as f32;
+                let ty = gy - y0 as f32;
+
+                let top = values[x0 + y0 * width] * (1.0 - tx) + values[x1 + y0 * width] * tx;
+                let bottom = values[x0 + y1 * width] * (1.0 - tx) + values[x1 + y1 * width] * tx;
+
+                (top * (1.0 - ty) + bottom * ty).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Mask::Grid { width, height, values } => *width > 0 && *height > 0 && values.len() == width * height,
+            _ => true,
+        }
+    }
+}
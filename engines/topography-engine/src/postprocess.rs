@@ -0,0 +1,119 @@
+use crate::Point;
+use crate::math::smoothstep;
+
+/*
+ * note:
+ * stages run in order over the normalized map, after normalization
+ * every stage maps [0, 1] into [0, 1]
+ */
+#[derive(Clone)]
+pub enum PostProcess {
+    // value^gamma, gamma > 1 widens lowlands, gamma < 1 widens highlands
+    Power(f32),
+    // piecewise-linear curve through control points (x = input, y = output)
+    Remap(Vec<Point>),
+    // quantized plateaus, softness [0, 1] from hard steps to a continuous ramp
+    Terrace { steps: usize, softness: f32 },
+    // everything below level becomes sea, the rest is stretched back to [0, 1]
+    SeaLevel(f32),
+    // flat histogram, evenly spaced contours by area
+    Equalize,
+}
+
+impl PostProcess {
+    pub fn apply(&self, map: &mut [f32]) {
+        match self {
+            PostProcess::Power(gamma) => {
+                for value in map.iter_mut() {
+                    *value = value.powf(*gamma);
+                }
+            }
+
+            PostProcess::Remap(points) => {
+                if points.is_empty() {
+                    return;
+                }
+
+                let mut points = points.clone();
+                points.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+                for value in map.iter_mut() {
+                    *value = PostProcess::remap(&points, *value).clamp(0.0, 1.0);
+                }
+            }
+
+            PostProcess::Terrace { steps, softness } => {
+                if *steps == 0 {
+                    return;
+                }
+
+                let steps_f = *steps as f32;
+                let softness = softness.clamp(0.0, 1.0);
+
+                for value in map.iter_mut() {
+                    let scaled = *value * steps_f;
+                    let base = scaled.floor();
+                    let ramp = smoothstep(1.0 - softness, 1.0, scaled - base);
+
+                    *value = ((base + ramp) / steps_f).min(1.0);
+                }
+            }
+
+            PostProcess::SeaLevel(level) => {
+                let level = level.clamp(0.0, 1.0);
+
+                if level >= 1.0 {
+                    map.fill(0.0);
+                    return;
+                }
+
+                for value in map.iter_mut() {
+                    *value = (value.max(level) - level) / (1.0 - level);
+                }
+            }
+
+            PostProcess::Equalize => {
+                let mut sorted = map.to_vec();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+
+                let last = (sorted.len().max(2) - 1) as f32;
+
+                for value in map.iter_mut() {
+                    // average rank of equal values, so flat areas stay flat
+                    let first = sorted.partition_point(|v| v < value);
+                    let end = sorted.partition_point(|v| v <= value);
+
+                    *value = (first + end - 1) as f32 * 0.5 / last;
+                }
+            }
+        }
+    }
+
+    fn remap(points: &[Point], value: f32) -> f32 {
+        let first = points[0];
+        let last = points[points.len() - 1];
+
+        if value <= first.x {
+            return first.y;
+        }
+
+        if value >= last.x {
+            return last.y;
+        }
+
+        for w in points.windows(2) {
+            let (a, b) = (w[0], w[1]);
+
+            if value <= b.x {
+                if b.x - a.x <= 0.0 {
+                    return b.y;
+                }
+
+                let t = (value - a.x) / (b.x - a.x);
+                return a.y + t * (b.y - a.y);
+            }
+        }
+
+        last.y
+    }
+}
@@ -1,6 +1,9 @@
-use crate::{Point, Segment};
+use crate::{CriticalPoints, Drainage, FlowDirection, Mask, Point, PostProcess, River, Segment};
 use crate::random::random_f32;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 /*
  * references:
  * 
@@ -35,6 +38,9 @@ pub struct Topography {
     hurst: f32, // decay factor for roughness [0, 1]
     blur_radious: usize,
     blur_iterations: usize,
+
+    mask: Option<Mask>, // applied before normalization
+    post_processes: Vec<PostProcess>, // applied in order after normalization
 }
 
 impl Topography {
@@ -51,6 +57,9 @@ impl Topography {
             hurst,
             blur_radious,
             blur_iterations,
+
+            mask: None,
+            post_processes: Vec::new(),
         }
     }
 
@@ -58,21 +67,80 @@ impl Topography {
         &self.map
     }
 
+    // side of the square map
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
     fn normalize(&mut self) {
         let map = &mut self.map;
         
         let min = map.iter().cloned().fold(f32::INFINITY, f32::min);
         let max = map.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
 
+        // flat map (e.g. an all-zero mask), all sea level instead of 0 / 0
+        if max <= min {
+            map.fill(0.0);
+            return;
+        }
+
         for value in map {
             *value = (*value - min) / (max - min);
         }
     }
 
+    pub fn set_mask(&mut self, mask: Mask) {
+        assert!(mask.is_valid(), "Error: mask grid must be width * height values");
+
+        self.mask = Some(mask);
+    }
+
+    pub fn clear_mask(&mut self) {
+        self.mask = None;
+    }
+
+    fn apply_mask(&mut self) {
+        let Some(mask) = &self.mask else {
+            return;
+        };
+
+        let size = self.size;
+        let last = (size - 1) as f32;
+        let map = &mut self.map;
+
+        // pull terrain down towards the current minimum, which becomes sea level once normalized
+        let min = map.iter().cloned().fold(f32::INFINITY, f32::min);
+
+        for y in 0..size {
+            for x in 0..size {
+                let factor = mask.sample(x as f32 / last, y as f32 / last);
+                let value = &mut map[x + y * size];
+
+                *value = min + (*value - min) * factor;
+            }
+        }
+    }
+
+    pub fn add_post_process(&mut self, stage: PostProcess) {
+        self.post_processes.push(stage);
+    }
+
+    pub fn clear_post_processes(&mut self) {
+        self.post_processes.clear();
+    }
+
+    fn apply_post_processes(&mut self) {
+        for stage in &self.post_processes {
+            stage.apply(&mut self.map);
+        }
+    }
+
     pub fn compute(&mut self) {
         self.diamond_square();
         self.blur_box(self.blur_radious, self.blur_iterations);
+        self.apply_mask();
         self.normalize();
+        self.apply_post_processes();
         self.compute_borders();
     }
 
@@ -86,6 +154,18 @@ impl Topography {
         self.levels
     }
 
+    pub fn drainage(&self, direction: FlowDirection) -> Drainage {
+        Drainage::new(&self.map, self.size, direction)
+    }
+
+    pub fn rivers(&self, direction: FlowDirection, threshold: f32) -> Vec<River> {
+        self.drainage(direction).rivers(threshold)
+    }
+
+    pub fn critical_points(&self, min_prominence: f32) -> CriticalPoints {
+        CriticalPoints::new(&self.map, self.size, min_prominence)
+    }
+
     fn diamond_square(&mut self) {
         let size = self.size;
         let last = self.size - 1;
@@ -176,21 +256,29 @@ impl Topography {
         }
     }
 
+    #[cfg(not(feature = "parallel"))]
     fn compute_borders(&mut self) {
-        let last = self.size - 1;
+        self.borders = (0..self.levels).map(|level| self.level_borders(level)).collect();
+    }
 
-        for level in 0..self.levels {
-            let threshold = level as f32 / self.levels as f32;
-            let mut segments = Vec::new();
+    #[cfg(feature = "parallel")]
+    fn compute_borders(&mut self) {
+        // levels are independent, collect keeps them in order
+        self.borders = (0..self.levels).into_par_iter().map(|level| self.level_borders(level)).collect();
+    }
 
-            for y in 0..last {
-                for x in 0..last {
-                    self.marching_squares(x, y, threshold, &mut segments);
-                }
-            }
+    fn level_borders(&self, level: usize) -> Vec<Vec<Point>> {
+        let last = self.size - 1;
+        let threshold = level as f32 / self.levels as f32;
+        let mut segments = Vec::new();
 
-            self.borders[level] = Topography::segments_to_polylines(segments);
+        for y in 0..last {
+            for x in 0..last {
+                self.marching_squares(x, y, threshold, &mut segments);
+            }
         }
+
+        Topography::segments_to_polylines(segments)
     }
 
     fn marching_squares(&self, x: usize, y: usize, threshold: f32, segments: &mut Vec<Segment>) {
@@ -310,29 +398,37 @@ impl Topography {
         let mut temp = self.map.clone();
 
         for _ in 0..iterations {
-            for y in 0..size {
-                for x in 0..size {
-                    let mut sum = 0.0;
-                    let mut count = 0;
-
-                    for dy in -(radius as isize)..=(radius as isize) {
-                        for dx in -(radius as isize)..=(radius as isize) {
-                            let nx = x as isize + dx;
-                            let ny = y as isize + dy;
-
-                            if nx >= 0 && ny >= 0 &&
-                            nx < size as isize && ny < size as isize {
-                                sum += self.map[nx as usize + ny as usize * size];
-                                count += 1;
-                            }
-                        }
-                    }
+            let map = &self.map;
+
+            #[cfg(not(feature = "parallel"))]
+            temp.chunks_mut(size).enumerate().for_each(|(y, row)| Topography::blur_row(map, size, radius, y, row));
+
+            #[cfg(feature = "parallel")]
+            temp.par_chunks_mut(size).enumerate().for_each(|(y, row)| Topography::blur_row(map, size, radius, y, row));
+
+            std::mem::swap(&mut self.map, &mut temp);
+        }
+    }
+
+    fn blur_row(map: &[f32], size: usize, radius: usize, y: usize, row: &mut [f32]) {
+        for (x, value) in row.iter_mut().enumerate() {
+            let mut sum = 0.0;
+            let mut count = 0;
 
-                    temp[x + y * size] = sum / count as f32;
+            for dy in -(radius as isize)..=(radius as isize) {
+                for dx in -(radius as isize)..=(radius as isize) {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+
+                    if nx >= 0 && ny >= 0 &&
+                    nx < size as isize && ny < size as isize {
+                        sum += map[nx as usize + ny as usize * size];
+                        count += 1;
+                    }
                 }
             }
 
-            std::mem::swap(&mut self.map, &mut temp);
+            *value = sum / count as f32;
         }
     }
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
+use std::time::Instant;
+
 use minifb::{Key, MouseMode, Window, WindowOptions};
-use boids_engine::{World, Vec2};
+use boids_engine::{Obstacle, Params, World, Vec2};
 
 const WIDTH: usize = 640;
 const HEIGHT: usize = 360;
@@ -20,7 +22,14 @@ fn main() {
     window.set_target_fps(60);
 
     let mut world = World::new(200, WIDTH as f32, HEIGHT as f32);
-    let dt = 1.0 / 60.0;
+    world.add_obstacle(Obstacle::Circle {
+        center: Vec2::new(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0),
+        radius: 40.0,
+    });
+    world.add_predator(Vec2::new(0.0, 0.0));
+    world.add_species(Params { max_speed: 120.0, ..Params::default() }, 60);
+    world.set_trail_length(12);
+    let mut last = Instant::now();
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
 
@@ -31,18 +40,44 @@ fn main() {
             world.clear_attractor();
         }
 
-        world.step(dt);
+        let now = Instant::now();
+        let alpha = world.advance((now - last).as_secs_f32());
+        last = now;
 
         clear(&mut buffer, 0x000000);
 
+        for obstacle in world.get_obstacles() {
+            draw_obstacle(&mut buffer, obstacle, 0x444444);
+        }
+
+        for (_, trail) in world.get_trails() {
+            for segment in trail.windows(2) {
+                draw_line(&mut buffer, segment[0], segment[1], 0x333333);
+            }
+        }
+
         for boid in world.get_boids() {
+            let params = world.get_species_params(boid.species);
+            let color = if boid.species == 0 { 0xFFFFFF } else { 0x00AAFF };
+
+            draw_boid(
+                &mut buffer,
+                boid.interpolate(alpha),
+                boid.heading(),
+                shade(color, boid.speed() / params.max_speed),
+            );
+        }
+
+        for predator in world.get_predators() {
+            let pos = predator.interpolate(alpha);
+
             draw_pixel(
                 &mut buffer,
-                boid.pos.x as i32,
-                boid.pos.y as i32,
+                pos.x as i32,
+                pos.y as i32,
                 WIDTH as i32,
                 HEIGHT as i32,
-                0xFFFFFF,
+                0xFF0000,
             );
         }
 
@@ -59,6 +94,48 @@ fn clear(buffer: &mut [u32], color: u32) {
     }
 }
 
+fn draw_obstacle(buffer: &mut [u32], obstacle: &Obstacle, color: u32) {
+    if let Obstacle::Circle { center, radius } = obstacle {
+        let steps = (radius * 8.0) as usize;
+
+        for i in 0..steps {
+            let angle = i as f32 / steps as f32 * std::f32::consts::TAU;
+            let x = center.x + radius * angle.cos();
+            let y = center.y + radius * angle.sin();
+
+            draw_pixel(buffer, x as i32, y as i32, WIDTH as i32, HEIGHT as i32, color);
+        }
+    }
+}
+
+// triangle pointing along the heading
+fn draw_boid(buffer: &mut [u32], pos: Vec2, heading: f32, color: u32) {
+    let tip = pos + Vec2::new(heading.cos(), heading.sin()) * 4.0;
+    let left = pos + Vec2::new((heading + 2.5).cos(), (heading + 2.5).sin()) * 3.0;
+    let right = pos + Vec2::new((heading - 2.5).cos(), (heading - 2.5).sin()) * 3.0;
+
+    draw_line(buffer, tip, left, color);
+    draw_line(buffer, left, right, color);
+    draw_line(buffer, right, tip, color);
+}
+
+fn draw_line(buffer: &mut [u32], a: Vec2, b: Vec2, color: u32) {
+    let steps = (b - a).magnitude().ceil().max(1.0) as usize;
+
+    for i in 0..=steps {
+        let p = a + (b - a) * (i as f32 / steps as f32);
+        draw_pixel(buffer, p.x as i32, p.y as i32, WIDTH as i32, HEIGHT as i32, color);
+    }
+}
+
+// dimmer when slower, t in [0, 1]
+fn shade(color: u32, t: f32) -> u32 {
+    let factor = 0.35 + 0.65 * t.clamp(0.0, 1.0);
+    let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * factor) as u32) << shift;
+
+    channel(16) | channel(8) | channel(0)
+}
+
 fn draw_pixel(
     buffer: &mut [u32],
     x: i32,
@@ -74,6 +74,7 @@ fn clear(buffer: &mut [u32], color: u32) {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_line(
     buffer: &mut [u32],
     x0: i32,
@@ -1,5 +1,6 @@
 use wasm_bindgen::prelude::*;
-use boids_engine::{World, Vec2};
+use crate::TopographyAPI;
+use boids_engine::{BUFFER_LAYOUT, BUFFER_STRIDE, Boid, Boundary, Falloff, FieldKind, FlowField, ForceField, Formation, Path, PathKind, Integrator, Interaction, NeighborMode, Obstacle, Params, Region, TopographyFlow, World, Vec2};
 
 #[wasm_bindgen]
 pub struct BoidsAPI {
@@ -15,14 +16,135 @@ impl BoidsAPI {
         }
     }
 
-    pub fn set_params(&mut self, param: &str, value: f32) {
-        self.engine.set_params(param, value);
+    // throws on unknown names and out of range values, see get_params_schema
+    pub fn set_params(&mut self, param: &str, value: f32) -> Result<(), JsError> {
+        self.engine.set_params(param, value).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    pub fn get_param(&self, param: &str) -> Result<f32, JsError> {
+        self.engine.get_param(param).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    // [{"name","kind","min","max","default","description"},...]
+    // without neighbor mode and boundary, see set_neighbor_mode and set_boundary
+    pub fn get_params_schema(&self) -> Result<String, JsError> {
+        serde_json::to_string(&Params::descriptors()).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    // new species with default params, returns its id
+    pub fn add_species(&mut self, n: usize) -> usize {
+        self.engine.add_species(Params::default(), n)
+    }
+
+    pub fn set_species_params(&mut self, species: usize, param: &str, value: f32) -> Result<(), JsError> {
+        self.engine.set_species_params(species, param, value).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    pub fn get_species_param(&self, species: usize, param: &str) -> Result<f32, JsError> {
+        self.engine.get_species_param(species, param).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    // interaction: "flock" | "separate" | "ignore"
+    pub fn set_interaction(&mut self, a: usize, b: usize, interaction: &str) {
+        let count = self.engine.species_count();
+
+        if a >= count || b >= count {
+            return;
+        }
+
+        let interaction = match interaction {
+            "flock" => Interaction::Flock,
+            "separate" => Interaction::Separate,
+            "ignore" => Interaction::Ignore,
+            _ => return,
+        };
+
+        self.engine.set_interaction(a, b, interaction);
+    }
+
+    pub fn get_species(&self) -> Vec<u32> {
+        self.engine.get_boids().iter().map(|boid| boid.species as u32).collect()
+    }
+
+    // species 0 boid, returns its index
+    pub fn add_boid(&mut self, x: f32, y: f32, vx: f32, vy: f32) -> usize {
+        self.engine.add_boid(Boid::new(Vec2::new(x, y), Vec2::new(vx, vy)))
+    }
+
+    // the last boid takes the removed index
+    pub fn remove_boid(&mut self, index: usize) -> bool {
+        self.engine.remove_boid(index).is_some()
+    }
+
+    pub fn spawn_at(&mut self, x: f32, y: f32, count: usize) {
+        self.engine.spawn_at(Vec2::new(x, y), count);
+    }
+
+    pub fn set_population(&mut self, n: usize) {
+        self.engine.set_population(n);
+    }
+
+    pub fn get_population(&self) -> usize {
+        self.engine.get_boids().len()
+    }
+
+    // e.g. on window resize, rescale = false clamps boids inside instead
+    pub fn resize(&mut self, width: f32, height: f32, rescale: bool) {
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        self.engine.resize(width, height, rescale);
+    }
+
+    // mode: "metric" | "topological"
+    pub fn set_neighbor_mode(&mut self, mode: &str) {
+        match mode {
+            "metric" => self.engine.set_neighbor_mode(NeighborMode::Metric),
+            "topological" => self.engine.set_neighbor_mode(NeighborMode::Topological),
+            _ => (),
+        }
     }
 
     pub fn set_bounce_on_edge(&mut self, bounce: bool) {
         self.engine.set_bounce_on_edge(bounce);
     }
 
+    // mode: "bounce" | "wrap" | "soft" | "open", margin and force only used by "soft" and must not be negative
+    pub fn set_boundary(&mut self, mode: &str, margin: f32, force: f32) {
+        let boundary = match mode {
+            "bounce" => Boundary::Bounce,
+            "wrap" => Boundary::Wrap,
+            "soft" if margin >= 0.0 && force >= 0.0 => Boundary::SoftMargin { margin, force },
+            "open" => Boundary::Open,
+            _ => return,
+        };
+
+        self.engine.set_boundary(boundary);
+    }
+
+    pub fn set_circle_containment(&mut self, x: f32, y: f32, radius: f32) {
+        if radius > 0.0 {
+            self.engine.set_containment(Some(Region::Circle { center: Vec2::new(x, y), radius }));
+        }
+    }
+
+    // points: [x0,y0,x1,y1,...], at least 3 points
+    pub fn set_polygon_containment(&mut self, points: &[f32]) {
+        let points: Vec<Vec2> = points
+            .chunks_exact(2)
+            .map(|p| Vec2::new(p[0], p[1]))
+            .collect();
+
+        if points.len() >= 3 {
+            self.engine.set_containment(Some(Region::Polygon { points }));
+        }
+    }
+
+    pub fn clear_containment(&mut self) {
+        self.engine.set_containment(None);
+    }
+
     pub fn get_boids(&self) -> Vec<f32> {
         let boids = self.engine.get_boids();
         let mut buffer = Vec::with_capacity(boids.len() * 2);
@@ -35,6 +157,116 @@ impl BoidsAPI {
         buffer
     }
 
+    // {"groups":[{"size","centroid":{x,y},"mean_velocity":{x,y}},...],"labels":[...],"polarization","angular_momentum","mean_nearest_distance"}
+    pub fn get_stats(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.engine.get_stats()).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    // group index per boid, same order as get_stats groups (largest first).
+    // recomputes the stats, use the labels of get_stats when both are needed
+    pub fn get_groups(&self) -> Vec<u32> {
+        self.engine.get_stats().labels.iter().map(|label| *label as u32).collect()
+    }
+
+    // positions kept per boid by step, 0 disables trails
+    pub fn set_trail_length(&mut self, length: usize) {
+        self.engine.set_trail_length(length);
+    }
+
+    // [x0,y0,x1,y1,...] every polyline one after the other, split with get_trail_lengths
+    pub fn get_trails(&self) -> Vec<f32> {
+        let mut buffer = Vec::new();
+
+        for (_, polyline) in self.engine.get_trails() {
+            for pos in polyline {
+                buffer.push(pos.x);
+                buffer.push(pos.y);
+            }
+        }
+
+        buffer
+    }
+
+    // points in every polyline of get_trails
+    pub fn get_trail_lengths(&self) -> Vec<u32> {
+        self.engine.get_trails().iter().map(|(_, polyline)| polyline.len() as u32).collect()
+    }
+
+    // boid index of every polyline in get_trails
+    pub fn get_trail_boids(&self) -> Vec<u32> {
+        self.engine.get_trails().iter().map(|(boid, _)| *boid as u32).collect()
+    }
+
+    // radians, atan2(vy, vx)
+    pub fn get_headings(&self) -> Vec<f32> {
+        self.engine.get_boids().iter().map(|boid| boid.heading()).collect()
+    }
+
+    pub fn get_speeds(&self) -> Vec<f32> {
+        self.engine.get_boids().iter().map(|boid| boid.speed()).collect()
+    }
+
+    // perceived in the last step
+    pub fn get_neighbor_counts(&self) -> Vec<u32> {
+        self.engine.get_boids().iter().map(|boid| boid.neighbors as u32).collect()
+    }
+
+    // perceived neighbours per px^2, last step
+    pub fn get_densities(&self) -> Vec<f32> {
+        self.engine.get_boids().iter().map(|boid| boid.density).collect()
+    }
+
+    /*
+     * note:
+     * zero-copy access, new Float32Array(memory.buffer, buffer_ptr(), buffer_len()).
+     * the view is only valid until the next call that allocates (e.g. spawning boids),
+     * so take it again every frame
+     */
+    pub fn buffer_ptr(&self) -> usize {
+        self.engine.get_buffer().as_ptr() as usize
+    }
+
+    // in floats
+    pub fn buffer_len(&self) -> usize {
+        self.engine.get_buffer().len()
+    }
+
+    // same as above, as a view over wasm memory
+    pub fn buffer_view(&self) -> js_sys::Float32Array {
+        let buffer = self.engine.get_buffer();
+
+        // safe as long as the view is dropped before wasm memory grows, see buffer_ptr
+        unsafe { js_sys::Float32Array::view(buffer) }
+    }
+
+    pub fn buffer_stride(&self) -> usize {
+        BUFFER_STRIDE
+    }
+
+    // {"stride":9,"fields":{"x":0,"y":1,...}}, offsets in floats
+    pub fn buffer_layout(&self) -> String {
+        let fields: serde_json::Map<String, serde_json::Value> = BUFFER_LAYOUT
+            .iter()
+            .map(|(name, offset)| (name.to_string(), (*offset).into()))
+            .collect();
+
+        serde_json::json!({ "stride": BUFFER_STRIDE, "fields": fields }).to_string()
+    }
+
+    // [x0,y0,x1,y1,...] blended between the last two steps, alpha from advance
+    pub fn get_boids_interpolated(&self, alpha: f32) -> Vec<f32> {
+        let boids = self.engine.get_boids();
+        let mut buffer = Vec::with_capacity(boids.len() * 2);
+
+        for boid in boids {
+            let pos = boid.interpolate(alpha);
+            buffer.push(pos.x);
+            buffer.push(pos.y);
+        }
+
+        buffer
+    }
+
     pub fn set_attractor(&mut self, x: f32, y: f32) {
         self.engine.set_attractor(Some(Vec2::new(x, y)));
     }
@@ -43,7 +275,243 @@ impl BoidsAPI {
         self.engine.clear_attractor();
     }
 
+    // falloff: "constant" | "linear" | "inverse_square"
+    pub fn add_attractor(&mut self, x: f32, y: f32, strength: f32, radius: f32, falloff: &str) -> Option<usize> {
+        let falloff = Self::falloff(falloff)?;
+        Some(self.engine.add_field(ForceField::new(FieldKind::Attractor, Vec2::new(x, y), strength, radius, falloff)))
+    }
+
+    pub fn add_repeller(&mut self, x: f32, y: f32, strength: f32, radius: f32, falloff: &str) -> Option<usize> {
+        let falloff = Self::falloff(falloff)?;
+        Some(self.engine.add_field(ForceField::new(FieldKind::Repeller, Vec2::new(x, y), strength, radius, falloff)))
+    }
+
+    pub fn move_field(&mut self, id: usize, x: f32, y: f32) -> bool {
+        self.engine.move_field(id, Vec2::new(x, y))
+    }
+
+    pub fn set_field_strength(&mut self, id: usize, strength: f32) -> bool {
+        match self.engine.get_field(id) {
+            Some(field) => self.engine.update_field(id, ForceField { strength, ..*field }),
+            None => false,
+        }
+    }
+
+    pub fn remove_field(&mut self, id: usize) -> bool {
+        self.engine.remove_field(id)
+    }
+
+    pub fn clear_fields(&mut self) {
+        self.engine.clear_fields();
+    }
+
+    pub fn add_circle_obstacle(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        self.engine.add_obstacle(Obstacle::Circle { center: Vec2::new(x, y), radius })
+    }
+
+    pub fn add_rect_obstacle(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> usize {
+        self.engine.add_obstacle(Obstacle::Rect {
+            min: Vec2::new(x0.min(x1), y0.min(y1)),
+            max: Vec2::new(x0.max(x1), y0.max(y1)),
+        })
+    }
+
+    // [x0,y0,x1,y1,...]
+    pub fn add_polygon_obstacle(&mut self, points: Vec<f32>) -> usize {
+        let points = points
+            .chunks_exact(2)
+            .map(|p| Vec2::new(p[0], p[1]))
+            .collect();
+
+        self.engine.add_obstacle(Obstacle::Polygon { points })
+    }
+
+    pub fn add_segment_obstacle(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> usize {
+        self.engine.add_obstacle(Obstacle::Segment { a: Vec2::new(x0, y0), b: Vec2::new(x1, y1) })
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.engine.clear_obstacles();
+    }
+
+    pub fn add_predator(&mut self, x: f32, y: f32) -> usize {
+        self.engine.add_predator(Vec2::new(x, y))
+    }
+
+    pub fn clear_predators(&mut self) {
+        self.engine.clear_predators();
+    }
+
+    pub fn get_predators(&self) -> Vec<f32> {
+        let predators = self.engine.get_predators();
+        let mut buffer = Vec::with_capacity(predators.len() * 2);
+
+        for predator in predators {
+            buffer.push(predator.pos.x);
+            buffer.push(predator.pos.y);
+        }
+
+        buffer
+    }
+
+    // [predator0,boid0,predator1,boid1,...] since the last call
+    pub fn take_captures(&mut self) -> Vec<u32> {
+        let captures = self.engine.take_captures();
+        let mut buffer = Vec::with_capacity(captures.len() * 2);
+
+        for capture in captures {
+            buffer.push(capture.predator as u32);
+            buffer.push(capture.boid as u32);
+        }
+
+        buffer
+    }
+
+    // vectors: [x0,y0,x1,y1,...] row major, stretched over the whole world
+    pub fn set_flow_grid(&mut self, cols: usize, rows: usize, vectors: &[f32]) {
+        if cols == 0 || rows == 0 || vectors.len() != cols * rows * 2 {
+            return;
+        }
+
+        let vectors = vectors.chunks_exact(2).map(|v| Vec2::new(v[0], v[1])).collect();
+        self.engine.set_flow_field(FlowField::grid(cols, rows, vectors));
+    }
+
+    // scale: feature size in px
+    pub fn set_curl_noise_flow(&mut self, scale: f32, seed: u32) {
+        if scale > 0.0 {
+            self.engine.set_flow_field(FlowField::curl_noise(scale, seed));
+        }
+    }
+
+    // flow: "uphill" | "downhill" | "contour", the map is stretched over the whole world
+    pub fn set_topography_flow(&mut self, topography: &TopographyAPI, flow: &str) {
+        let flow = match flow {
+            "uphill" => TopographyFlow::Uphill,
+            "downhill" => TopographyFlow::Downhill,
+            "contour" => TopographyFlow::Contour,
+            _ => return,
+        };
+
+        self.engine.set_flow_field(FlowField::from_topography(topography.engine(), flow));
+    }
+
+    pub fn clear_flow_field(&mut self) {
+        self.engine.clear_flow_field();
+    }
+
+    // points: [x0,y0,x1,y1,...], at least 2 points, returns the leader index
+    pub fn add_leader(&mut self, points: &[f32], spline: bool, looped: bool, speed: f32) -> Option<usize> {
+        let points: Vec<Vec2> = points
+            .chunks_exact(2)
+            .map(|p| Vec2::new(p[0], p[1]))
+            .collect();
+
+        if points.len() < 2 {
+            return None;
+        }
+
+        let kind = if spline { PathKind::Spline } else { PathKind::Waypoints };
+        Some(self.engine.add_leader(Path::new(points, kind, looped), speed))
+    }
+
+    // formation: "v" | "line" | "circle"
+    pub fn set_formation(&mut self, leader: usize, formation: &str, spacing: f32) {
+        let formation = match formation {
+            "v" => Formation::V,
+            "line" => Formation::Line,
+            "circle" => Formation::Circle,
+            _ => return,
+        };
+
+        if leader < self.engine.get_leaders().len() {
+            self.engine.set_formation(leader, formation, spacing);
+        }
+    }
+
+    // the count closest boids not following anyone yet
+    pub fn recruit_followers(&mut self, leader: usize, count: usize) {
+        if leader < self.engine.get_leaders().len() {
+            self.engine.recruit_followers(leader, count);
+        }
+    }
+
+    pub fn release_followers(&mut self, leader: usize) {
+        self.engine.release_followers(leader);
+    }
+
+    pub fn clear_leaders(&mut self) {
+        self.engine.clear_leaders();
+    }
+
+    // [x0,y0,x1,y1,...]
+    pub fn get_leaders(&self) -> Vec<f32> {
+        let leaders = self.engine.get_leaders();
+        let mut buffer = Vec::with_capacity(leaders.len() * 2);
+
+        for leader in leaders {
+            buffer.push(leader.pos.x);
+            buffer.push(leader.pos.y);
+        }
+
+        buffer
+    }
+
     pub fn step(&mut self, dt: f32) {
         self.engine.step(dt);
     }
+
+    // compact binary state, e.g. for sessionStorage between page navigations
+    pub fn snapshot(&self) -> Result<Vec<u8>, JsError> {
+        self.engine.to_bytes().map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    pub fn restore(&mut self, snapshot: &[u8]) -> Result<(), JsError> {
+        let world = World::from_bytes(snapshot).map_err(|e| JsError::new(&e.to_string()))?;
+        self.engine.restore(world);
+        Ok(())
+    }
+
+    pub fn snapshot_json(&self) -> Result<String, JsError> {
+        self.engine.to_json().map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    pub fn restore_json(&mut self, snapshot: &str) -> Result<(), JsError> {
+        let world = World::from_json(snapshot).map_err(|e| JsError::new(&e.to_string()))?;
+        self.engine.restore(world);
+        Ok(())
+    }
+
+    // real elapsed seconds, returns the interpolation alpha
+    pub fn advance(&mut self, real_dt: f32) -> f32 {
+        self.engine.advance(real_dt)
+    }
+
+    pub fn set_fixed_dt(&mut self, dt: f32) {
+        if dt > 0.0 {
+            self.engine.set_fixed_dt(dt);
+        }
+    }
+
+    pub fn set_max_substeps(&mut self, max_substeps: usize) {
+        self.engine.set_max_substeps(max_substeps);
+    }
+
+    pub fn set_integrator(&mut self, integrator: &str) {
+        match integrator {
+            "euler" => self.engine.set_integrator(Integrator::Euler),
+            "semi_implicit_euler" => self.engine.set_integrator(Integrator::SemiImplicitEuler),
+            "verlet" => self.engine.set_integrator(Integrator::Verlet),
+            _ => (),
+        }
+    }
+
+    fn falloff(falloff: &str) -> Option<Falloff> {
+        match falloff {
+            "constant" => Some(Falloff::Constant),
+            "linear" => Some(Falloff::Linear),
+            "inverse_square" => Some(Falloff::InverseSquare),
+            _ => None,
+        }
+    }
 }
\ No newline at end of file
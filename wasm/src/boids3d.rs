@@ -0,0 +1,83 @@
+use wasm_bindgen::prelude::*;
+use boids_engine::{Bounds3D, Vec2, Vec3, World3D};
+
+#[wasm_bindgen]
+pub struct Boids3DAPI {
+    engine: World3D,
+}
+
+#[wasm_bindgen]
+impl Boids3DAPI {
+    #[wasm_bindgen(constructor)]
+    pub fn new(n: usize, width: f32, height: f32, depth: f32) -> Self {
+        Boids3DAPI {
+            engine: World3D::new(n, width, height, depth),
+        }
+    }
+
+    // same params as BoidsAPI::set_params
+    pub fn set_params(&mut self, param: &str, value: f32) -> Result<(), JsError> {
+        self.engine.set_params(param, value).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    // bounds: "box" | "toroidal"
+    pub fn set_bounds(&mut self, bounds: &str) {
+        match bounds {
+            "box" => self.engine.set_bounds(Bounds3D::Box),
+            "toroidal" => self.engine.set_bounds(Bounds3D::Toroidal),
+            _ => (),
+        }
+    }
+
+    pub fn set_attractor(&mut self, x: f32, y: f32, z: f32) {
+        self.engine.set_attractor(Some(Vec3::new(x, y, z)));
+    }
+
+    pub fn clear_attractor(&mut self) {
+        self.engine.set_attractor(None);
+    }
+
+    // [x0,y0,z0,x1,y1,z1,...]
+    pub fn get_boids(&self) -> Vec<f32> {
+        let boids = self.engine.get_boids();
+        let mut buffer = Vec::with_capacity(boids.len() * 3);
+
+        for boid in boids {
+            buffer.push(boid.pos.x);
+            buffer.push(boid.pos.y);
+            buffer.push(boid.pos.z);
+        }
+
+        buffer
+    }
+
+    // [x0,y0,scale0,...] on screen, scale 1.0 at z = 0 and smaller with depth
+    pub fn get_projected(&self, focal_length: f32) -> Vec<f32> {
+        let projection = self.engine.projection(focal_length);
+        let boids = self.engine.get_boids();
+        let mut buffer = Vec::with_capacity(boids.len() * 3);
+
+        for boid in boids {
+            let (screen, scale) = projection.project(boid.pos).unwrap_or((Vec2::new(boid.pos.x, boid.pos.y), 0.0));
+
+            buffer.push(screen.x);
+            buffer.push(screen.y);
+            buffer.push(scale);
+        }
+
+        buffer
+    }
+
+    // boid indices from the farthest to the nearest, to draw back to front
+    pub fn get_depth_order(&self) -> Vec<u32> {
+        let boids = self.engine.get_boids();
+        let mut order: Vec<u32> = (0..boids.len() as u32).collect();
+
+        order.sort_by(|a, b| boids[*b as usize].pos.z.total_cmp(&boids[*a as usize].pos.z));
+        order
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        self.engine.step(dt);
+    }
+}
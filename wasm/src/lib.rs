@@ -1,5 +1,7 @@
 mod boids;
+mod boids3d;
 mod topography;
 
 pub use boids::BoidsAPI;
+pub use boids3d::Boids3DAPI;
 pub use topography::TopographyAPI;
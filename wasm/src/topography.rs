@@ -1,11 +1,18 @@
 use wasm_bindgen::prelude::*;
-use topography_engine::Topography;
+use topography_engine::{CriticalKind, FlowDirection, Mask, Point, PostProcess, Topography};
 
 #[wasm_bindgen]
 pub struct TopographyAPI {
     engine: Topography,
 }
 
+impl TopographyAPI {
+    // for BoidsAPI flow fields
+    pub(crate) fn engine(&self) -> &Topography {
+        &self.engine
+    }
+}
+
 #[wasm_bindgen]
 impl TopographyAPI {
     #[wasm_bindgen(constructor)]
@@ -15,6 +22,62 @@ impl TopographyAPI {
         }
     }
 
+    pub fn set_radial_mask(&mut self, center_x: f32, center_y: f32, radius: f32, falloff: f32) {
+        self.engine.set_mask(Mask::Radial {
+            center: Point::new(center_x, center_y),
+            radius,
+            falloff,
+        });
+    }
+
+    pub fn set_rounded_rect_mask(&mut self, margin: f32, corner_radius: f32, falloff: f32) {
+        self.engine.set_mask(Mask::RoundedRect { margin, corner_radius, falloff });
+    }
+
+    pub fn set_grid_mask(&mut self, width: usize, height: usize, values: Vec<f32>) {
+        let mask = Mask::Grid { width, height, values };
+
+        if !mask.is_valid() {
+            return;
+        }
+
+        self.engine.set_mask(mask);
+    }
+
+    pub fn clear_mask(&mut self) {
+        self.engine.clear_mask();
+    }
+
+    pub fn add_power_curve(&mut self, gamma: f32) {
+        self.engine.add_post_process(PostProcess::Power(gamma));
+    }
+
+    // [x0,y0,x1,y1,...] control points
+    pub fn add_remap_curve(&mut self, points: Vec<f32>) {
+        let points = points
+            .chunks_exact(2)
+            .map(|p| Point::new(p[0], p[1]))
+            .collect();
+
+        self.engine.add_post_process(PostProcess::Remap(points));
+    }
+
+    pub fn add_terrace(&mut self, steps: usize, softness: f32) {
+        self.engine.add_post_process(PostProcess::Terrace { steps, softness });
+    }
+
+    pub fn add_sea_level(&mut self, level: f32) {
+        self.engine.add_post_process(PostProcess::SeaLevel(level));
+    }
+
+    pub fn add_equalize(&mut self) {
+        self.engine.add_post_process(PostProcess::Equalize);
+    }
+
+    pub fn clear_post_processes(&mut self) {
+        self.engine.clear_post_processes();
+    }
+
     pub fn compute(&mut self) {
         self.engine.compute();
     }
@@ -45,4 +108,73 @@ impl TopographyAPI {
 
         buffer
     }
+
+    pub fn get_flow_accumulation(&self, d_infinity: bool) -> Vec<f32> {
+        self.engine.drainage(Self::flow_direction(d_infinity)).get_accumulation().clone()
+    }
+
+    pub fn get_rivers(&self, threshold: f32, d_infinity: bool) -> Vec<f32> {
+        let rivers = self.engine.rivers(Self::flow_direction(d_infinity), threshold);
+
+        // [x0,y0,flow0,x1,y1,flow1,-1.0,-1.0,-1.0,...] (-1,-1,-1 = polyline separator)
+        let mut buffer = Vec::new();
+
+        for river in rivers {
+            for (point, flow) in river.points.iter().zip(river.flow) {
+                buffer.push(point.x);
+                buffer.push(point.y);
+                buffer.push(flow);
+            }
+
+            buffer.push(-1.0);
+            buffer.push(-1.0);
+            buffer.push(-1.0);
+        }
+
+        buffer
+    }
+
+    pub fn get_critical_points(&self, min_prominence: f32) -> Vec<f32> {
+        let critical = self.engine.critical_points(min_prominence);
+
+        // [kind,x,y,height,prominence,...] (kind: 0 = peak, 1 = pit, 2 = saddle)
+        let mut buffer = Vec::with_capacity(critical.points.len() * 5);
+
+        for point in critical.points {
+            buffer.push(match point.kind {
+                CriticalKind::Peak => 0.0,
+                CriticalKind::Pit => 1.0,
+                CriticalKind::Saddle => 2.0,
+            });
+            buffer.push(point.point.x);
+            buffer.push(point.point.y);
+            buffer.push(point.height);
+            buffer.push(point.prominence);
+        }
+
+        buffer
+    }
+
+    pub fn get_saddle_links(&self, min_prominence: f32) -> Vec<u32> {
+        let critical = self.engine.critical_points(min_prominence);
+
+        // [saddle,extremum_a,extremum_b,...] indices into get_critical_points
+        let mut buffer = Vec::with_capacity(critical.links.len() * 3);
+
+        for link in critical.links {
+            buffer.push(link.saddle as u32);
+            buffer.push(link.extrema[0] as u32);
+            buffer.push(link.extrema[1] as u32);
+        }
+
+        buffer
+    }
+
+    fn flow_direction(d_infinity: bool) -> FlowDirection {
+        if d_infinity {
+            FlowDirection::DInfinity
+        } else {
+            FlowDirection::D8
+        }
+    }
 }
\ No newline at end of file